@@ -4,9 +4,11 @@ extern crate engine;
 
 use cpuprofiler::PROFILER;
 use criterion::{criterion_group, criterion_main, Criterion};
+use engine::city::config::DistributionParams;
 use engine::v0::config::{
     BackgroundViralParticleParams, BehaviorParameters, DiseaseParameters, DiseaseSpreadParameters,
-    MapParams, MiscParams, ShopperParams, WorldConfig,
+    ExhaleKernel, InfectionModel, InfectionRadiusParams, InfectiousStateValues, MapParams,
+    MaskFiltrationTable, MiscParams, ShopperParams, StoreChoice, WorldConfig,
 };
 use engine::v0::geometry::BoundingBox;
 use engine::v0::wasm_view::WorldView;
@@ -15,9 +17,14 @@ use engine::v0::wasm_view::WorldView;
 fn run_infection_radius_spread() {
     let world_config = WorldConfig {
         disease_parameters: DiseaseParameters {
-            exposed_period_ticks: 0,
-            infectious_period_ticks: 345,
-            spread_parameters: DiseaseSpreadParameters::InfectionRadius(3.2),
+            exposed_period_ticks: DistributionParams::Fixed(0.0),
+            infectious_period_ticks: DistributionParams::Fixed(345.0),
+            waning_immunity: None,
+            symptoms: None,
+            spread_parameters: DiseaseSpreadParameters::InfectionRadius(InfectionRadiusParams {
+                radius: 3.2,
+                radius_multipliers: InfectiousStateValues::default(),
+            }),
         },
         behavior_parameters: BehaviorParameters::BrownianMotion,
         bounding_box: BoundingBox {
@@ -32,7 +39,18 @@ fn run_infection_radius_spread() {
         misc_parameters: MiscParams {
             fraction_mask: 0.0,
             fraction_n95_mask: 0.0,
+            fraction_cloth_mask: 0.0,
+            fraction_surgical_mask: 0.0,
+            fraction_respirator_mask: 0.0,
+            mask_fit: None,
+            mask_filtration: MaskFiltrationTable::default(),
         },
+        interventions: vec![],
+        policies: vec![],
+        vaccination: None,
+        clock: None,
+        distancing: None,
+        walking_speed: None,
     };
 
     let rng = Box::new(rand::thread_rng());
@@ -43,16 +61,70 @@ fn run_infection_radius_spread() {
     }
 }
 
+// Creates a world where people are as dense as in run_infection_radius_spread.
+fn make_large_infection_radius_world(num_people: usize) -> WorldView {
+    let scale = ((num_people / 200) as f32).sqrt();
+
+    let world_config = WorldConfig {
+        disease_parameters: DiseaseParameters {
+            exposed_period_ticks: DistributionParams::Fixed(0.0),
+            infectious_period_ticks: DistributionParams::Fixed(345.0),
+            waning_immunity: None,
+            symptoms: None,
+            spread_parameters: DiseaseSpreadParameters::InfectionRadius(InfectionRadiusParams {
+                radius: 3.2,
+                radius_multipliers: InfectiousStateValues::default(),
+            }),
+        },
+        behavior_parameters: BehaviorParameters::BrownianMotion,
+        bounding_box: BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: (400.0 * scale) as usize,
+            right: (600.0 * scale) as usize,
+        },
+        num_people,
+        num_initially_infected: num_people / 10,
+        map_params: None,
+        misc_parameters: MiscParams {
+            fraction_mask: 0.0,
+            fraction_n95_mask: 0.0,
+            fraction_cloth_mask: 0.0,
+            fraction_surgical_mask: 0.0,
+            fraction_respirator_mask: 0.0,
+            mask_fit: None,
+            mask_filtration: MaskFiltrationTable::default(),
+        },
+        interventions: vec![],
+        policies: vec![],
+        vaccination: None,
+        clock: None,
+        distancing: None,
+        walking_speed: None,
+    };
+
+    let rng = Box::new(rand::thread_rng());
+    WorldView::new(world_config, rng).unwrap()
+}
+
 fn run_viral_particle_spread() {
     let world_config = WorldConfig {
         disease_parameters: DiseaseParameters {
-            exposed_period_ticks: 115,
-            infectious_period_ticks: 345,
+            exposed_period_ticks: DistributionParams::Fixed(115.0),
+            infectious_period_ticks: DistributionParams::Fixed(345.0),
+            waning_immunity: None,
+            symptoms: None,
             spread_parameters: DiseaseSpreadParameters::BackgroundViralParticle(
                 BackgroundViralParticleParams {
                     exhale_radius: 9.0,
+                    exhale_kernel: ExhaleKernel::Uniform,
                     decay_rate: 0.05,
+                    ventilation: vec![],
+                    walls: false,
+                    air_movement: None,
                     infection_risk_per_particle: 0.001_9,
+                    infection_model: InfectionModel::PerTick,
+                    exhale_multipliers: InfectiousStateValues::default(),
                 },
             ),
         },
@@ -69,7 +141,18 @@ fn run_viral_particle_spread() {
         misc_parameters: MiscParams {
             fraction_mask: 0.0,
             fraction_n95_mask: 0.0,
+            fraction_cloth_mask: 0.0,
+            fraction_surgical_mask: 0.0,
+            fraction_respirator_mask: 0.0,
+            mask_fit: None,
+            mask_filtration: MaskFiltrationTable::default(),
         },
+        interventions: vec![],
+        policies: vec![],
+        vaccination: None,
+        clock: None,
+        distancing: None,
+        walking_speed: None,
     };
 
     let rng = Box::new(rand::thread_rng());
@@ -83,13 +166,21 @@ fn run_viral_particle_spread() {
 fn run_viral_particle_spread_shopping() {
     let world_config = WorldConfig {
         disease_parameters: DiseaseParameters {
-            exposed_period_ticks: 15 * 60,
-            infectious_period_ticks: 45 * 60,
+            exposed_period_ticks: DistributionParams::Fixed(15.0 * 60.0),
+            infectious_period_ticks: DistributionParams::Fixed(45.0 * 60.0),
+            waning_immunity: None,
+            symptoms: None,
             spread_parameters: DiseaseSpreadParameters::BackgroundViralParticle(
                 BackgroundViralParticleParams {
                     exhale_radius: 9.0,
+                    exhale_kernel: ExhaleKernel::Uniform,
                     decay_rate: 0.055,
+                    ventilation: vec![],
+                    walls: false,
+                    air_movement: None,
                     infection_risk_per_particle: 0.000_13,
+                    infection_model: InfectionModel::PerTick,
+                    exhale_multipliers: InfectiousStateValues::default(),
                 },
             ),
         },
@@ -99,6 +190,11 @@ fn run_viral_particle_spread_shopping() {
             init_supply_high_range: 450.0,
             supplies_bought_per_trip: 1800.0,
             fraction_dual_shopper_households: 0.5,
+            symptomatic_isolation: None,
+            store_choice: StoreChoice::Nearest,
+            max_store_occupancy: None,
+            opening_hours: None,
+            fraction_senior_households: 0.0,
         }),
         bounding_box: BoundingBox {
             bottom: 0,
@@ -116,7 +212,18 @@ fn run_viral_particle_spread_shopping() {
         misc_parameters: MiscParams {
             fraction_mask: 0.0,
             fraction_n95_mask: 0.0,
+            fraction_cloth_mask: 0.0,
+            fraction_surgical_mask: 0.0,
+            fraction_respirator_mask: 0.0,
+            mask_fit: None,
+            mask_filtration: MaskFiltrationTable::default(),
         },
+        interventions: vec![],
+        policies: vec![],
+        vaccination: None,
+        clock: None,
+        distancing: None,
+        walking_speed: None,
     };
 
     let rng = Box::new(rand::thread_rng());
@@ -147,6 +254,13 @@ fn criterion_benchmark(c: &mut Criterion) {
 
     PROFILER.lock().unwrap().stop().unwrap();
 
+    for num_people in &[10_000, 100_000] {
+        let mut world = make_large_infection_radius_world(*num_people);
+        group.bench_function(format!("radius_spread_step_{}_people", num_people), |b| {
+            b.iter(|| world.step())
+        });
+    }
+
     run_viral_particle_spread();
     // flame::dump_html(&mut File::create("/tmp/flame-graph.html").unwrap()).unwrap();
 
@@ -64,6 +64,7 @@ fn make_config() -> CityConfig {
             self_quarantine_after_infectious_h: 48,
         },
         initial_latent_percent: 0.01, // 100k people, 1k initially infected
+        vaccination: None,
     }
 }
 
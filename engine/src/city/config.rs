@@ -1,7 +1,16 @@
-#[derive(Debug)]
+use crate::vaccination::VaccinationParams;
+use serde::{Deserialize, Serialize};
+
+// Shared with the v0 engine, which reads it from its configs.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
 pub enum DistributionParams {
+    #[serde(rename = "fixed")]
     Fixed(f64),
+    #[serde(rename = "uniform")]
+    Uniform(/*low*/ f64, /*high*/ f64),
+    #[serde(rename = "normal")]
     Normal(/*mean*/ f64, /*stddev*/ f64),
+    #[serde(rename = "log_normal")]
     LogNormal(/*mean*/ f64, /*stddev*/ f64),
 }
 
@@ -47,10 +56,22 @@ pub struct BuildingConfig {
     pub square_footage: u32,
 }
 
+// Attributes that vaccination campaigns can prioritize.
+#[derive(Debug, Copy, Clone)]
+pub enum VaccinationAttribute {
+    // People who go to a school or an office.
+    Commuter,
+    // People who stay at home all day.
+    StaysHome,
+}
+
 pub struct CityConfig {
     // The number of people is determined by the number of residences, max occupancy
     pub buildings: Vec<BuildingConfig>,
 
     pub disease_params: DiseaseParams,
     pub initial_latent_percent: f32,
+
+    // Times in the campaign are in hours.
+    pub vaccination: Option<VaccinationParams<VaccinationAttribute>>,
 }
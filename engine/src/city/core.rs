@@ -7,6 +7,13 @@ impl CityState {
     pub fn step(&mut self) {
         self.hour += 1;
 
+        // Step 0: give out the vaccine doses scheduled for this hour
+        if let Some(campaign) = &mut self.vaccination_campaign {
+            for &id in campaign.next_doses(self.hour) {
+                self.people[id].vaccinated_hour = Some(self.hour);
+            }
+        }
+
         // Step 1: update disease states
         let disease_params = &self.disease_params;
         let hour = self.hour;
@@ -67,6 +74,7 @@ impl CityState {
         // We can imagine that the people are lined up in a space filling Hilbert curve, so it is
         // sufficient for infectious people to infect their left/right N neighbors, instead of
         // needing to do a more complicated 2D search.
+        let vaccine_efficacy = self.vaccination_campaign.as_ref().map(|c| c.efficacy);
         let mut people_id_by_building_id = HashMap::<usize, Vec<usize>>::new();
         self.people.iter().for_each(|p| {
             let entry = people_id_by_building_id.entry(p.current_building_id);
@@ -86,6 +94,14 @@ impl CityState {
                     DiseaseStatus::InfSym => disease_params.transmission_rate_for_symptomatic,
                 };
 
+                let p_transmit = match &vaccine_efficacy {
+                    Some(efficacy) => {
+                        p_transmit
+                            * efficacy.infectiousness_multiplier(p.vaccinated_hour, hour) as f64
+                    }
+                    None => p_transmit,
+                };
+
                 let neighbor_idxs = [
                     (i + people_ids.len() - 1) % people_ids.len(),
                     (i + 1) % people_ids.len(),
@@ -95,8 +111,17 @@ impl CityState {
                 for &neighbor_idx in neighbor_idxs.iter() {
                     let p2 = &mut self.people[people_ids[neighbor_idx]];
                     if let DiseaseStatus::Susceptible = p2.disease_state.status {
+                        let p_infect = match &vaccine_efficacy {
+                            Some(efficacy) => {
+                                p_transmit
+                                    * efficacy.susceptibility_multiplier(p2.vaccinated_hour, hour)
+                                        as f64
+                            }
+                            None => p_transmit,
+                        };
+
                         // Roll dice on infection
-                        if rng.gen_bool(p_transmit) {
+                        if rng.gen_bool(p_infect) {
                             p2.disease_state.status = DiseaseStatus::Latent;
                             p2.disease_state.next_status_time =
                                 hour + (24.0 * disease_params.latent_period_d.sample(rng)) as usize;
@@ -1,6 +1,8 @@
 use crate::city::config::{
     BuildingConfig, BuildingType, CityConfig, DiseaseParams, DistributionParams,
+    VaccinationAttribute,
 };
+use crate::vaccination::{VaccinationCampaign, VaccinationCandidate, VaccinationPriority};
 use derivative::Derivative;
 use rand::distributions::Distribution;
 use rand::seq::SliceRandom;
@@ -35,6 +37,13 @@ impl DistributionParams {
     pub(crate) fn sample(&self, rng: &mut dyn RngCore) -> f64 {
         match self {
             DistributionParams::Fixed(x) => *x,
+            DistributionParams::Uniform(low, high) => {
+                if low < high {
+                    rng.gen_range(*low, *high)
+                } else {
+                    *low
+                }
+            }
             DistributionParams::Normal(mu, stddev) => {
                 let dist = rand_distr::Normal::new(*mu, *stddev).unwrap();
                 dist.sample(rng)
@@ -49,10 +58,20 @@ impl DistributionParams {
     pub(crate) fn mean(&self) -> f64 {
         match self {
             DistributionParams::Fixed(x) => *x,
+            DistributionParams::Uniform(low, high) => (low + high) / 2.0,
             DistributionParams::Normal(mu, _) => *mu,
             DistributionParams::LogNormal(_, _) => unimplemented!(),
         }
     }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            DistributionParams::Fixed(_) => true,
+            DistributionParams::Uniform(low, high) => low <= high,
+            DistributionParams::Normal(_, stddev) => *stddev >= 0.0,
+            DistributionParams::LogNormal(_, stddev) => *stddev >= 0.0,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -89,6 +108,8 @@ pub struct PersonState {
     pub(crate) schedule: [usize; 24],
 
     pub(crate) num_people_infected: usize,
+
+    pub(crate) vaccinated_hour: Option<usize>, // Hour of when the person was vaccinated
 }
 
 #[derive(Debug)]
@@ -107,6 +128,8 @@ pub struct CityState {
 
     pub(crate) people: Vec<PersonState>,
     pub(crate) buildings: Vec<Building>,
+
+    pub(crate) vaccination_campaign: Option<VaccinationCampaign>,
 }
 
 #[derive(Debug, Serialize)]
@@ -116,6 +139,7 @@ pub struct CityStateSnapshot {
     num_latent: usize,
     num_infectious: usize,
     num_recovered: usize,
+    num_vaccinated: usize,
     r_eff: f32,
 }
 
@@ -147,6 +171,7 @@ impl CityState {
                     },
                     schedule,
                     num_people_infected: 0,
+                    vaccinated_hour: None,
                 });
             }
         });
@@ -189,6 +214,26 @@ impl CityState {
             p.schedule = make_schedule(p.home_building_id, p.secondary_building_id);
         }
 
+        let vaccination_campaign = config.vaccination.as_ref().map(|params| {
+            let candidates = people
+                .iter()
+                .map(|p| VaccinationCandidate {
+                    household: p.home_building_id,
+                    has_attribute: match params.priority {
+                        VaccinationPriority::ByAttribute(VaccinationAttribute::Commuter) => {
+                            p.secondary_building_id.is_some()
+                        }
+                        VaccinationPriority::ByAttribute(VaccinationAttribute::StaysHome) => {
+                            p.secondary_building_id.is_none()
+                        }
+                        _ => false,
+                    },
+                })
+                .collect::<Vec<_>>();
+
+            VaccinationCampaign::new(params, 24, &candidates, &mut rng)
+        });
+
         let buildings = config
             .buildings
             .into_iter()
@@ -201,6 +246,7 @@ impl CityState {
             hour: 0,
             people,
             buildings,
+            vaccination_campaign,
         }
     }
 
@@ -247,6 +293,10 @@ impl CityState {
             num_latent,
             num_infectious,
             num_recovered,
+            num_vaccinated: self
+                .vaccination_campaign
+                .as_ref()
+                .map_or(0, |campaign| campaign.num_vaccinated()),
             r_eff,
         }
     }
@@ -9,11 +9,11 @@ extern crate wasm_bindgen;
 
 pub mod city;
 pub mod v0;
+pub mod vaccination;
 
+use crate::v0::checkpoint::{SeededRng, SimulationRng, WorldCheckpoint};
 use crate::v0::config::WorldConfig;
 use crate::v0::wasm_view::WorldView;
-use rand::{RngCore, SeedableRng};
-use rand_chacha::ChaCha8Rng;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -26,10 +26,19 @@ extern "C" {
 pub fn create_world(config: &JsValue, maybe_seed: Option<u32>) -> WorldView {
     let world_config: WorldConfig = config.into_serde().expect("failed to parse");
 
-    let rng: Box<dyn RngCore> = if let Some(seed_val) = maybe_seed {
-        Box::new(ChaCha8Rng::seed_from_u64(seed_val as u64))
+    let rng: Box<dyn SimulationRng> = if let Some(seed_val) = maybe_seed {
+        Box::new(SeededRng::new(seed_val as u64))
     } else {
         Box::new(rand::thread_rng())
     };
     WorldView::new(world_config, rng).expect("failed to create world")
 }
+
+#[wasm_bindgen]
+pub fn load_world(checkpoint_json: &str) -> Result<WorldView, JsValue> {
+    let checkpoint: WorldCheckpoint = serde_json::from_str(checkpoint_json)
+        .map_err(|e| JsValue::from_str(&format!("failed to parse checkpoint: {}", e)))?;
+
+    WorldView::load(checkpoint)
+        .map_err(|e| JsValue::from_str(&format!("failed to load world: {}", e)))
+}
@@ -1,6 +1,8 @@
+use engine::city::config::DistributionParams;
 use engine::v0::config::{
     BackgroundViralParticleParams, BehaviorParameters, DiseaseParameters, DiseaseSpreadParameters,
-    MapParams, MiscParams, ShopperParams, WorldConfig,
+    ExhaleKernel, InfectionModel, InfectionRadiusParams, InfectiousStateValues, MapParams,
+    MaskFiltrationTable, MiscParams, ShopperParams, StoreChoice, WorldConfig,
 };
 use engine::v0::geometry::BoundingBox;
 
@@ -14,9 +16,16 @@ impl ConfigGenerator for InfectionRadius {
     fn gen(&mut self) -> WorldConfig {
         WorldConfig {
             disease_parameters: DiseaseParameters {
-                exposed_period_ticks: 0,
-                infectious_period_ticks: 345,
-                spread_parameters: DiseaseSpreadParameters::InfectionRadius(3.2),
+                exposed_period_ticks: DistributionParams::Fixed(0.0),
+                infectious_period_ticks: DistributionParams::Fixed(345.0),
+                waning_immunity: None,
+                symptoms: None,
+                spread_parameters: DiseaseSpreadParameters::InfectionRadius(
+                    InfectionRadiusParams {
+                        radius: 3.2,
+                        radius_multipliers: InfectiousStateValues::default(),
+                    },
+                ),
             },
             behavior_parameters: BehaviorParameters::BrownianMotion,
             bounding_box: BoundingBox {
@@ -31,7 +40,18 @@ impl ConfigGenerator for InfectionRadius {
             misc_parameters: MiscParams {
                 fraction_mask: 0.0,
                 fraction_n95_mask: 0.0,
+                fraction_cloth_mask: 0.0,
+                fraction_surgical_mask: 0.0,
+                fraction_respirator_mask: 0.0,
+                mask_fit: None,
+                mask_filtration: MaskFiltrationTable::default(),
             },
+            interventions: vec![],
+            policies: vec![],
+            vaccination: None,
+            clock: None,
+            distancing: None,
+            walking_speed: None,
         }
     }
 }
@@ -42,13 +62,21 @@ impl ConfigGenerator for ViralParticle {
     fn gen(&mut self) -> WorldConfig {
         WorldConfig {
             disease_parameters: DiseaseParameters {
-                exposed_period_ticks: 115,
-                infectious_period_ticks: 345,
+                exposed_period_ticks: DistributionParams::Fixed(115.0),
+                infectious_period_ticks: DistributionParams::Fixed(345.0),
+                waning_immunity: None,
+                symptoms: None,
                 spread_parameters: DiseaseSpreadParameters::BackgroundViralParticle(
                     BackgroundViralParticleParams {
                         exhale_radius: 9.0,
+                        exhale_kernel: ExhaleKernel::Uniform,
                         decay_rate: 0.05,
+                        ventilation: vec![],
+                        walls: false,
+                        air_movement: None,
                         infection_risk_per_particle: 0.001_9,
+                        infection_model: InfectionModel::PerTick,
+                        exhale_multipliers: InfectiousStateValues::default(),
                     },
                 ),
             },
@@ -65,7 +93,18 @@ impl ConfigGenerator for ViralParticle {
             misc_parameters: MiscParams {
                 fraction_mask: 0.0,
                 fraction_n95_mask: 0.0,
+                fraction_cloth_mask: 0.0,
+                fraction_surgical_mask: 0.0,
+                fraction_respirator_mask: 0.0,
+                mask_fit: None,
+                mask_filtration: MaskFiltrationTable::default(),
             },
+            interventions: vec![],
+            policies: vec![],
+            vaccination: None,
+            clock: None,
+            distancing: None,
+            walking_speed: None,
         }
     }
 }
@@ -94,13 +133,21 @@ impl ConfigGenerator for ViralParticleShoppingSolo {
 
         WorldConfig {
             disease_parameters: DiseaseParameters {
-                exposed_period_ticks: 15 * 60,
-                infectious_period_ticks: 45 * 60,
+                exposed_period_ticks: DistributionParams::Fixed(15.0 * 60.0),
+                infectious_period_ticks: DistributionParams::Fixed(45.0 * 60.0),
+                waning_immunity: None,
+                symptoms: None,
                 spread_parameters: DiseaseSpreadParameters::BackgroundViralParticle(
                     BackgroundViralParticleParams {
                         exhale_radius: 9.0,
+                        exhale_kernel: ExhaleKernel::Uniform,
                         decay_rate: 0.055,
+                        ventilation: vec![],
+                        walls: false,
+                        air_movement: None,
                         infection_risk_per_particle: 0.000_13,
+                        infection_model: InfectionModel::PerTick,
+                        exhale_multipliers: InfectiousStateValues::default(),
                     },
                 ),
             },
@@ -110,6 +157,11 @@ impl ConfigGenerator for ViralParticleShoppingSolo {
                 init_supply_high_range: 450.0,
                 supplies_bought_per_trip: 1800.0,
                 fraction_dual_shopper_households,
+                symptomatic_isolation: None,
+                store_choice: StoreChoice::Nearest,
+                max_store_occupancy: None,
+                opening_hours: None,
+                fraction_senior_households: 0.0,
             }),
             bounding_box: BoundingBox {
                 bottom: 0,
@@ -127,7 +179,18 @@ impl ConfigGenerator for ViralParticleShoppingSolo {
             misc_parameters: MiscParams {
                 fraction_mask: 0.0,
                 fraction_n95_mask: 0.0,
+                fraction_cloth_mask: 0.0,
+                fraction_surgical_mask: 0.0,
+                fraction_respirator_mask: 0.0,
+                mask_fit: None,
+                mask_filtration: MaskFiltrationTable::default(),
             },
+            interventions: vec![],
+            policies: vec![],
+            vaccination: None,
+            clock: None,
+            distancing: None,
+            walking_speed: None,
         }
     }
 }
@@ -156,13 +219,21 @@ impl ConfigGenerator for ViralParticleShoppingMaskRegular {
 
         WorldConfig {
             disease_parameters: DiseaseParameters {
-                exposed_period_ticks: 15 * 60,
-                infectious_period_ticks: 45 * 60,
+                exposed_period_ticks: DistributionParams::Fixed(15.0 * 60.0),
+                infectious_period_ticks: DistributionParams::Fixed(45.0 * 60.0),
+                waning_immunity: None,
+                symptoms: None,
                 spread_parameters: DiseaseSpreadParameters::BackgroundViralParticle(
                     BackgroundViralParticleParams {
                         exhale_radius: 9.0,
+                        exhale_kernel: ExhaleKernel::Uniform,
                         decay_rate: 0.055,
+                        ventilation: vec![],
+                        walls: false,
+                        air_movement: None,
                         infection_risk_per_particle: 0.000_4,
+                        infection_model: InfectionModel::PerTick,
+                        exhale_multipliers: InfectiousStateValues::default(),
                     },
                 ),
             },
@@ -172,6 +243,11 @@ impl ConfigGenerator for ViralParticleShoppingMaskRegular {
                 init_supply_high_range: 450.0,
                 supplies_bought_per_trip: 1800.0,
                 fraction_dual_shopper_households: 0.0,
+                symptomatic_isolation: None,
+                store_choice: StoreChoice::Nearest,
+                max_store_occupancy: None,
+                opening_hours: None,
+                fraction_senior_households: 0.0,
             }),
             bounding_box: BoundingBox {
                 bottom: 0,
@@ -189,7 +265,18 @@ impl ConfigGenerator for ViralParticleShoppingMaskRegular {
             misc_parameters: MiscParams {
                 fraction_mask,
                 fraction_n95_mask: 0.0,
+                fraction_cloth_mask: 0.0,
+                fraction_surgical_mask: 0.0,
+                fraction_respirator_mask: 0.0,
+                mask_fit: None,
+                mask_filtration: MaskFiltrationTable::default(),
             },
+            interventions: vec![],
+            policies: vec![],
+            vaccination: None,
+            clock: None,
+            distancing: None,
+            walking_speed: None,
         }
     }
 }
@@ -218,13 +305,21 @@ impl ConfigGenerator for ViralParticleShoppingMaskN95 {
 
         WorldConfig {
             disease_parameters: DiseaseParameters {
-                exposed_period_ticks: 15 * 60,
-                infectious_period_ticks: 45 * 60,
+                exposed_period_ticks: DistributionParams::Fixed(15.0 * 60.0),
+                infectious_period_ticks: DistributionParams::Fixed(45.0 * 60.0),
+                waning_immunity: None,
+                symptoms: None,
                 spread_parameters: DiseaseSpreadParameters::BackgroundViralParticle(
                     BackgroundViralParticleParams {
                         exhale_radius: 9.0,
+                        exhale_kernel: ExhaleKernel::Uniform,
                         decay_rate: 0.055,
+                        ventilation: vec![],
+                        walls: false,
+                        air_movement: None,
                         infection_risk_per_particle: 0.000_4,
+                        infection_model: InfectionModel::PerTick,
+                        exhale_multipliers: InfectiousStateValues::default(),
                     },
                 ),
             },
@@ -234,6 +329,11 @@ impl ConfigGenerator for ViralParticleShoppingMaskN95 {
                 init_supply_high_range: 450.0,
                 supplies_bought_per_trip: 1800.0,
                 fraction_dual_shopper_households: 0.0,
+                symptomatic_isolation: None,
+                store_choice: StoreChoice::Nearest,
+                max_store_occupancy: None,
+                opening_hours: None,
+                fraction_senior_households: 0.0,
             }),
             bounding_box: BoundingBox {
                 bottom: 0,
@@ -251,7 +351,18 @@ impl ConfigGenerator for ViralParticleShoppingMaskN95 {
             misc_parameters: MiscParams {
                 fraction_mask: 0.0,
                 fraction_n95_mask,
+                fraction_cloth_mask: 0.0,
+                fraction_surgical_mask: 0.0,
+                fraction_respirator_mask: 0.0,
+                mask_fit: None,
+                mask_filtration: MaskFiltrationTable::default(),
             },
+            interventions: vec![],
+            policies: vec![],
+            vaccination: None,
+            clock: None,
+            distancing: None,
+            walking_speed: None,
         }
     }
 }
@@ -280,13 +391,21 @@ impl ConfigGenerator for ViralParticleShoppingMaskSingleN95 {
 
         WorldConfig {
             disease_parameters: DiseaseParameters {
-                exposed_period_ticks: 15 * 60,
-                infectious_period_ticks: 45 * 60,
+                exposed_period_ticks: DistributionParams::Fixed(15.0 * 60.0),
+                infectious_period_ticks: DistributionParams::Fixed(45.0 * 60.0),
+                waning_immunity: None,
+                symptoms: None,
                 spread_parameters: DiseaseSpreadParameters::BackgroundViralParticle(
                     BackgroundViralParticleParams {
                         exhale_radius: 9.0,
+                        exhale_kernel: ExhaleKernel::Uniform,
                         decay_rate: 0.055,
+                        ventilation: vec![],
+                        walls: false,
+                        air_movement: None,
                         infection_risk_per_particle: 0.000_4,
+                        infection_model: InfectionModel::PerTick,
+                        exhale_multipliers: InfectiousStateValues::default(),
                     },
                 ),
             },
@@ -296,6 +415,11 @@ impl ConfigGenerator for ViralParticleShoppingMaskSingleN95 {
                 init_supply_high_range: 450.0,
                 supplies_bought_per_trip: 1800.0,
                 fraction_dual_shopper_households: 0.0,
+                symptomatic_isolation: None,
+                store_choice: StoreChoice::Nearest,
+                max_store_occupancy: None,
+                opening_hours: None,
+                fraction_senior_households: 0.0,
             }),
             bounding_box: BoundingBox {
                 bottom: 0,
@@ -313,7 +437,18 @@ impl ConfigGenerator for ViralParticleShoppingMaskSingleN95 {
             misc_parameters: MiscParams {
                 fraction_mask,
                 fraction_n95_mask: 0.0185,
+                fraction_cloth_mask: 0.0,
+                fraction_surgical_mask: 0.0,
+                fraction_respirator_mask: 0.0,
+                mask_fit: None,
+                mask_filtration: MaskFiltrationTable::default(),
             },
+            interventions: vec![],
+            policies: vec![],
+            vaccination: None,
+            clock: None,
+            distancing: None,
+            walking_speed: None,
         }
     }
 }
@@ -1,8 +1,10 @@
 extern crate clap;
 use clap::{App, Arg};
 use engine::v0::config::WorldConfig;
+use engine::v0::interventions::InterventionRecord;
+use engine::v0::triggers::PolicyActivation;
 use engine::v0::types::Mask;
-use engine::v0::wasm_view::{DiseaseState, State, WorldView};
+use engine::v0::wasm_view::{DiseaseState, StoreQueueStats, WorldView};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
@@ -20,24 +22,35 @@ struct EndingPersonState {
 struct EndingState {
     tick: usize,
     num_susceptible: usize,
+    num_exposed: usize,
+    num_infectious: usize,
     num_recovered: usize,
+    num_vaccinated: usize,
+    household_secondary_attack_rate: Option<f32>,
+    store_visits: Vec<usize>,
+    store_queue_stats: Vec<StoreQueueStats>,
     ending_people_state: Vec<EndingPersonState>,
 }
 
 mod generate_config;
 
 impl EndingState {
-    fn from_state(state: &State) -> Self {
+    fn from_world_view(world_view: &WorldView) -> Self {
+        let state = world_view.get_state();
+        // With waning immunity, a run can be cut off while the disease is still spreading, so
+        // there may be exposed and infectious people at the end.
         let mut num_susceptible = 0;
+        let mut num_exposed = 0;
+        let mut num_infectious = 0;
         let mut num_recovered = 0;
         for person in state.people.iter() {
             match person.disease_state {
                 DiseaseState::Susceptible => num_susceptible += 1,
+                DiseaseState::Exposed => num_exposed += 1,
+                DiseaseState::Presymptomatic
+                | DiseaseState::Asymptomatic
+                | DiseaseState::Symptomatic => num_infectious += 1,
                 DiseaseState::Recovered => num_recovered += 1,
-                DiseaseState::Exposed | DiseaseState::Infectious => panic!(
-                    "There should not be any {:?} people at the end",
-                    person.disease_state
-                ),
             }
         }
 
@@ -58,7 +71,13 @@ impl EndingState {
         EndingState {
             tick: state.tick,
             num_susceptible,
+            num_exposed,
+            num_infectious,
             num_recovered,
+            num_vaccinated: state.num_vaccinated,
+            household_secondary_attack_rate: world_view.get_household_secondary_attack_rate(),
+            store_visits: world_view.get_store_visits(),
+            store_queue_stats: world_view.get_store_queue_stats(),
             ending_people_state,
         }
     }
@@ -69,18 +88,27 @@ struct RunRecord {
     config: WorldConfig,
     // TODO: should we save the random seed?
     ending_state: EndingState,
+    fired_interventions: Vec<InterventionRecord>,
+    policy_activations: Vec<PolicyActivation>,
 }
 
-fn run_to_completion(world: &mut WorldView) -> usize {
+// Runs the world until nobody is exposed or infectious, or until max_ticks have passed.
+fn run_to_completion(world: &mut WorldView, max_ticks: usize) -> usize {
     loop {
         let tick = world.step();
+        if tick >= max_ticks {
+            return tick;
+        }
 
         if !world
             .get_state()
             .people
             .iter()
             .any(|p| match p.disease_state {
-                DiseaseState::Exposed | DiseaseState::Infectious => true,
+                DiseaseState::Exposed
+                | DiseaseState::Presymptomatic
+                | DiseaseState::Asymptomatic
+                | DiseaseState::Symptomatic => true,
                 DiseaseState::Susceptible | DiseaseState::Recovered => false,
             })
         {
@@ -92,6 +120,7 @@ fn run_to_completion(world: &mut WorldView) -> usize {
 fn concurrent_run_to_completion(
     mut generator: Box<dyn generate_config::ConfigGenerator>,
     mut output_file: File,
+    max_ticks: usize,
 ) {
     let (tx_config, rx_config) = crossbeam_channel::bounded::<WorldConfig>(0);
     let (tx_record, rx_record) = crossbeam_channel::bounded::<RunRecord>(0);
@@ -105,11 +134,13 @@ fn concurrent_run_to_completion(
                 let rng = Box::new(rand::thread_rng());
                 let mut world_view = WorldView::new(config.clone(), rng).unwrap();
 
-                run_to_completion(&mut world_view);
-                let ending_state = EndingState::from_state(&world_view.get_state());
+                run_to_completion(&mut world_view, max_ticks);
+                let ending_state = EndingState::from_world_view(&world_view);
                 let record = RunRecord {
                     config,
                     ending_state,
+                    fired_interventions: world_view.get_intervention_records().to_vec(),
+                    policy_activations: world_view.get_policy_activations().to_vec(),
                 };
 
                 tx_record_clone.send(record).unwrap();
@@ -178,6 +209,12 @@ fn main() {
                 .takes_value(true)
                 .possible_values(&config_generator_by_name.keys().cloned().collect::<Vec<_>>()),
         )
+        .arg(
+            Arg::with_name("max_ticks")
+                .long("max_ticks")
+                .takes_value(true)
+                .default_value("100000"),
+        )
         .arg(Arg::with_name("output_file").takes_value(true))
         .get_matches();
 
@@ -187,6 +224,12 @@ fn main() {
         .unwrap()
         .1;
 
+    let max_ticks = matches
+        .value_of("max_ticks")
+        .unwrap()
+        .parse::<usize>()
+        .expect("max_ticks must be a non-negative integer");
+
     let output_filename = if let Some(filename) = matches.value_of("output_file") {
         filename.to_string()
     } else {
@@ -199,5 +242,5 @@ fn main() {
         .open(&output_filename)
         .expect("failed to open file");
 
-    concurrent_run_to_completion(config_generator, file);
+    concurrent_run_to_completion(config_generator, file, max_ticks);
 }
@@ -0,0 +1,191 @@
+// Checkpointing support for the v0 engine.
+//
+// A checkpoint captures everything needed to resume a world: the people, the internal state of
+// the disease spreader and the person behavior, the map's path cache and the position of the RNG
+// stream. A world restored from a checkpoint produces the same results as one that was never
+// interrupted.
+
+use crate::v0::config::WorldConfig;
+use crate::v0::core::Person;
+use crate::v0::disease_spread::DiseaseSpreaderCheckpoint;
+use crate::v0::interventions::InterventionRecord;
+use crate::v0::maps::PathCacheEntry;
+use crate::v0::person_behavior::PersonBehaviorCheckpoint;
+use crate::v0::transmission::TransmissionEvent;
+use crate::v0::triggers::PolicyActivation;
+use crate::vaccination::VaccinationCampaign;
+use rand::rngs::ThreadRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+// RNG used to drive a world.
+// Only RNGs that can report their position in the stream can be checkpointed.
+pub trait SimulationRng: RngCore {
+    fn get_state(&self) -> Option<RngState> {
+        None
+    }
+}
+
+impl SimulationRng for ThreadRng {}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct RngState {
+    seed: u64,
+
+    // Offset from the start of the stream, in 32-bit words.
+    word_pos: u128,
+}
+
+// ChaCha8 RNG that remembers its seed so that its state can be saved and restored.
+pub struct SeededRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn from_state(state: &RngState) -> Self {
+        let mut rng = Self::new(state.seed);
+        rng.rng.set_word_pos(state.word_pos);
+        rng
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl SimulationRng for SeededRng {
+    fn get_state(&self) -> Option<RngState> {
+        Some(RngState {
+            seed: self.seed,
+            word_pos: self.rng.get_word_pos(),
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WorldCheckpoint {
+    pub(crate) config: WorldConfig,
+    pub(crate) tick: usize,
+    pub(crate) people: Vec<Person>,
+    pub(crate) transmission_events: Vec<TransmissionEvent>,
+    pub(crate) next_intervention_idx: usize,
+    pub(crate) intervention_records: Vec<InterventionRecord>,
+    pub(crate) active_policies: Vec<bool>,
+    pub(crate) policy_activations: Vec<PolicyActivation>,
+    pub(crate) vaccination_campaign: Option<VaccinationCampaign>,
+
+    pub(crate) disease_spreader: DiseaseSpreaderCheckpoint,
+    pub(crate) person_behavior: PersonBehaviorCheckpoint,
+
+    // Paths are lazily computed with the RNG, so they need to be restored for the RNG stream to
+    // line up.
+    pub(crate) path_cache: Vec<PathCacheEntry>,
+
+    pub(crate) rng_state: RngState,
+}
+
+impl WorldCheckpoint {
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v0::config::{DiseaseSpreadParameters, FomiteParams, InfectiousStateValues};
+    use crate::v0::core::World;
+    use crate::v0::test_util;
+    use anyhow::Result;
+
+    fn shopping_config() -> WorldConfig {
+        let mut config = test_util::shopping_config();
+        let airborne = config.disease_parameters.spread_parameters.clone();
+        config.disease_parameters.spread_parameters = DiseaseSpreadParameters::Composite(vec![
+            airborne,
+            DiseaseSpreadParameters::Fomite(FomiteParams {
+                deposit_rate: 1.0,
+                deposit_multipliers: InfectiousStateValues::default(),
+                pickup_fraction: 0.1,
+                infection_risk_per_unit: 0.01,
+                decay_rate: 0.01,
+                cleaning: None,
+            }),
+        ]);
+        config.misc_parameters.fraction_mask = 0.2;
+        config.misc_parameters.fraction_n95_mask = 0.1;
+        config
+    }
+
+    #[test]
+    fn test_rng_state_round_trip() {
+        let mut rng = SeededRng::new(10914);
+        for _ in 0..37 {
+            rng.next_u32();
+        }
+        let mut restored = SeededRng::from_state(&rng.get_state().unwrap());
+
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), restored.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_restored_world_matches_uninterrupted_world() -> Result<()> {
+        let mut uninterrupted = World::new(Box::new(SeededRng::new(10914)), shopping_config())?;
+        let mut interrupted = World::new(Box::new(SeededRng::new(10914)), shopping_config())?;
+
+        for _ in 0..150 {
+            uninterrupted.step();
+            interrupted.step();
+        }
+
+        let serialized = serde_json::to_string(&interrupted.checkpoint()?)?;
+        drop(interrupted);
+        let mut restored = World::from_checkpoint(serde_json::from_str(&serialized)?)?;
+        assert_eq!(restored.tick, 150);
+
+        for _ in 0..150 {
+            uninterrupted.step();
+            restored.step();
+        }
+
+        assert_eq!(
+            serde_json::to_string(&uninterrupted.checkpoint()?)?,
+            serde_json::to_string(&restored.checkpoint()?)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unseeded_world_cannot_checkpoint() -> Result<()> {
+        let world = World::new(Box::new(rand::thread_rng()), shopping_config())?;
+        assert!(world.checkpoint().is_err());
+
+        Ok(())
+    }
+}
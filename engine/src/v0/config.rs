@@ -1,7 +1,10 @@
 // Contains configuration files for the v0 engine.
 
+use crate::city::config::DistributionParams;
 use crate::v0::geometry::BoundingBox;
-use serde::{Deserialize, Serialize};
+use crate::v0::maps::MapElement;
+use crate::vaccination::VaccinationParams;
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct MapParams {
@@ -10,43 +13,497 @@ pub struct MapParams {
     pub num_people_per_household: u8,
 }
 
+// A value for each of the infectious disease states.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct InfectiousStateValues {
+    pub presymptomatic: f32,
+    pub asymptomatic: f32,
+    pub symptomatic: f32,
+}
+
+impl Default for InfectiousStateValues {
+    fn default() -> Self {
+        InfectiousStateValues {
+            presymptomatic: 1.0,
+            asymptomatic: 1.0,
+            symptomatic: 1.0,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Copy, Clone)]
+pub struct InfectionRadiusParams {
+    // People within this distance of an infectious person are exposed.
+    pub radius: f32,
+
+    // Multiplier on the radius for each infectious state.
+    #[serde(default)]
+    pub radius_multipliers: InfectiousStateValues,
+}
+
+// Deserializes either a plain radius, or the full infection radius parameters.
+// This keeps configs written before the radius could vary by disease state working.
+fn deserialize_infection_radius<'de, D>(deserializer: D) -> Result<InfectionRadiusParams, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RadiusOrParams {
+        Radius(f32),
+        Params(InfectionRadiusParams),
+    }
+
+    Ok(match RadiusOrParams::deserialize(deserializer)? {
+        RadiusOrParams::Radius(radius) => InfectionRadiusParams {
+            radius,
+            radius_multipliers: InfectiousStateValues::default(),
+        },
+        RadiusOrParams::Params(params) => params,
+    })
+}
+
+// Probability per tick that an infectious person exposes a susceptible person, as a function of
+// the distance between them.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum TransmissionCurve {
+    // p(d) = p0 * exp(-d / length_scale)
+    #[serde(rename = "exponential")]
+    Exponential { p0: f32, length_scale: f32 },
+
+    // p(d) = p_max / (1 + exp(steepness * (d - midpoint)))
+    #[serde(rename = "logistic")]
+    Logistic {
+        p_max: f32,
+        midpoint: f32,
+        steepness: f32,
+    },
+
+    // (distance, probability) points in increasing order of distance, linearly interpolated in
+    // between. Distances past the last point use its probability.
+    #[serde(rename = "table")]
+    Table(Vec<(f32, f32)>),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ProbabilisticRadiusParams {
+    // People further than this from an infectious person are never exposed by them.
+    pub max_distance: f32,
+
+    pub curve: TransmissionCurve,
+
+    // Multiplier on the transmission probability for each infectious state.
+    #[serde(default)]
+    pub infectiousness_multipliers: InfectiousStateValues,
+}
+
+// Transmission between people who live in the same household.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct HouseholdTransmissionParams {
+    // Probability per tick that an infectious person exposes each susceptible member of their
+    // household.
+    pub hazard_per_tick: f32,
+
+    // Multiplier on the hazard for each infectious state.
+    #[serde(default)]
+    pub infectiousness_multipliers: InfectiousStateValues,
+
+    // If set, people only expose each other while both of them are inside their household.
+    #[serde(default)]
+    pub only_at_home: bool,
+}
+
+// Fractions of viral particles that get through a mask when breathing in and out.
+// 1 means that the mask has no effect, 0 means that it blocks every particle.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct MaskFiltration {
+    pub inhale: f32,
+    pub exhale: f32,
+}
+
+// Filtration for each kind of mask, when the mask fits perfectly.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+#[serde(default)]
+pub struct MaskFiltrationTable {
+    pub none: MaskFiltration,
+    pub regular: MaskFiltration,
+    pub n95: MaskFiltration,
+    pub cloth: MaskFiltration,
+    pub surgical: MaskFiltration,
+    pub respirator: MaskFiltration,
+}
+
+impl Default for MaskFiltrationTable {
+    fn default() -> Self {
+        MaskFiltrationTable {
+            none: MaskFiltration {
+                inhale: 1.0,
+                exhale: 1.0,
+            },
+            regular: MaskFiltration {
+                inhale: 1.0,
+                exhale: 0.2,
+            },
+            n95: MaskFiltration {
+                inhale: 0.2,
+                exhale: 0.2,
+            },
+            cloth: MaskFiltration {
+                inhale: 0.8,
+                exhale: 0.5,
+            },
+            surgical: MaskFiltration {
+                inhale: 0.6,
+                exhale: 0.3,
+            },
+            respirator: MaskFiltration {
+                inhale: 0.05,
+                exhale: 0.1,
+            },
+        }
+    }
+}
+
+// How exhaled particles are spread over the cells in the exhale radius.
+// Every kernel puts as many particles into the world as the uniform kernel, and only changes where
+// they land.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Default)]
+pub enum ExhaleKernel {
+    #[default]
+    #[serde(rename = "uniform")]
+    Uniform,
+
+    // Density falls off with distance like a normal distribution with this standard deviation.
+    #[serde(rename = "gaussian")]
+    Gaussian { stddev: f32 },
+
+    // Density falls off with the inverse square of the distance. Distances are clamped to at
+    // least min_distance, so that the density near the person stays finite.
+    #[serde(rename = "inverse_square")]
+    InverseSquare { min_distance: f32 },
+
+    // Particles only land within half_angle_rad of the direction that the person last moved in.
+    #[serde(rename = "cone")]
+    Cone { half_angle_rad: f32 },
+}
+
+// How the air moves viral particles between cells each tick.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct AirMovementParams {
+    // Fraction of the difference in particles between neighboring cells that evens out each tick.
+    // Must be in [0, 0.25] to keep the diffusion stable.
+    pub diffusion_coefficient: f32,
+
+    // Fraction of each cell's particles that the wind carries to the next cell in x and y each
+    // tick. The absolute values must add up to at most 1.
+    pub wind_x: f32,
+    pub wind_y: f32,
+}
+
+// Cells that a ventilation rule applies to.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum VentilationTarget {
+    // Every cell of this kind of map element. Worlds without a map are all background.
+    #[serde(rename = "map_element")]
+    MapElement(MapElement),
+
+    // A single household or store, by its index in the map.
+    #[serde(rename = "household")]
+    Household(usize),
+    #[serde(rename = "store")]
+    Store(usize),
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct VentilationParams {
+    pub target: VentilationTarget,
+
+    // Replaces the global decay rate in the target's cells.
+    pub decay_rate: f32,
+
+    // Fraction of the particles that are cleared out by fresh air each tick, on top of decay.
+    #[serde(default)]
+    pub air_exchange_rate: f32,
+}
+
+// Converts an inhaled dose of viral particles into the probability of being infected.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum DoseResponse {
+    // P(infection) = 1 - exp(-dose / k)
+    #[serde(rename = "exponential")]
+    Exponential { k: f32 },
+
+    // P(infection) = 1 - (1 + dose * (2^(1 / alpha) - 1) / n50)^(-alpha)
+    // n50 is the dose at which half of the people are infected.
+    #[serde(rename = "beta_poisson")]
+    BetaPoisson { alpha: f32, n50: f32 },
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct DoseModelParams {
+    // Multiplier on the particles in a person's cell to get the dose that they inhale each tick.
+    pub breathing_rate: f32,
+
+    // Fraction of each person's accumulated dose that is cleared each tick.
+    #[serde(default)]
+    pub dose_decay_rate: f32,
+
+    pub dose_response: DoseResponse,
+}
+
+// How inhaled particles lead to infections.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Default)]
+pub enum InfectionModel {
+    // Each tick is an independent trial, with a probability of infection_risk_per_particle for
+    // each particle inhaled during the tick.
+    #[default]
+    #[serde(rename = "per_tick")]
+    PerTick,
+
+    // Each person accumulates the particles that they inhale over time, and their probability of
+    // having been infected follows the dose-response curve of their accumulated dose.
+    #[serde(rename = "dose")]
+    Dose(DoseModelParams),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct BackgroundViralParticleParams {
     // How far an infected person spreads background particles
     // TODO: this should vary as a function of PPE.
     pub exhale_radius: f32,
 
-    // TODO: add parameter for exhale density function: the amount of particles exhaled should
-    // not be uniform across the circle.
+    #[serde(default)]
+    pub exhale_kernel: ExhaleKernel,
 
     // What percentage of particles deactivate each tick.
     // 0 means that no particles are ever deactivated, 1 means that all particles are instantly
     // deactivated.
     pub decay_rate: f32,
 
+    // Decay and air exchange rates for parts of the map. Later rules take precedence over earlier
+    // ones for the cells that they share.
+    #[serde(default)]
+    pub ventilation: Vec<VentilationParams>,
+
+    // If set, the boundaries between map regions and wall cells block particles from being
+    // exhaled or carried across them. The doorways between households or stores and the road stay
+    // open.
+    #[serde(default)]
+    pub walls: bool,
+
+    // If set, particles spread out into neighboring cells and drift with the wind. Particles never
+    // leave the world, so only decay removes them.
+    #[serde(default)]
+    pub air_movement: Option<AirMovementParams>,
+
     // Probability per inhaled viral particle per tick that a susceptible person becomes
-    // infected.
+    // infected. Only used by the per-tick infection model.
     pub infection_risk_per_particle: f32,
+
+    #[serde(default)]
+    pub infection_model: InfectionModel,
+
+    // Multiplier on the particles exhaled by a person in each infectious state.
+    #[serde(default)]
+    pub exhale_multipliers: InfectiousStateValues,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum DiseaseSpreadParameters {
     #[serde(rename = "infection_radius")]
-    InfectionRadius(f32),
+    InfectionRadius(
+        #[serde(deserialize_with = "deserialize_infection_radius")] InfectionRadiusParams,
+    ),
 
     #[serde(rename = "background_viral_particle")]
     BackgroundViralParticle(BackgroundViralParticleParams),
+
+    // Like the infection radius, but exposures are random and less likely further away.
+    #[serde(rename = "probabilistic_radius")]
+    ProbabilisticRadius(ProbabilisticRadiusParams),
+
+    // Spread between members of the same household. Requires a map.
+    #[serde(rename = "household")]
+    Household(HouseholdTransmissionParams),
+
+    // Spread by touching contaminated surfaces in stores. Usually a route of a composite, next to
+    // an airborne spread model.
+    #[serde(rename = "fomite")]
+    Fomite(FomiteParams),
+
+    // Runs each of the spread models every tick, in order. People exposed by one route are no
+    // longer susceptible to the routes after it.
+    #[serde(rename = "composite")]
+    Composite(Vec<DiseaseSpreadParameters>),
+}
+
+// Deserializes either a plain number of ticks, which is treated as a fixed distribution, or a
+// distribution. This keeps configs written before periods could vary working.
+fn deserialize_ticks_distribution<'de, D>(deserializer: D) -> Result<DistributionParams, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TicksOrDistribution {
+        Ticks(usize),
+        Distribution(DistributionParams),
+    }
+
+    Ok(match TicksOrDistribution::deserialize(deserializer)? {
+        TicksOrDistribution::Ticks(ticks) => DistributionParams::Fixed(ticks as f64),
+        TicksOrDistribution::Distribution(distribution) => distribution,
+    })
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone)]
+pub struct WaningImmunityParams {
+    // How long a recovered person stays immune before they become susceptible again.
+    pub immunity_period_ticks: DistributionParams,
+
+    // Multiplier on the infection risk of a person who has lost their immunity.
+    // 1 means that they are as susceptible as someone who was never infected, 0 means that they
+    // can never be reinfected.
+    pub reinfection_susceptibility: f32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone)]
+pub struct SymptomParams {
+    // Fraction of infected people who never show symptoms.
+    pub fraction_asymptomatic: f32,
+
+    // How long people who go on to show symptoms are infectious before their symptoms start.
+    pub presymptomatic_period_ticks: DistributionParams,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct DiseaseParameters {
-    pub exposed_period_ticks: usize,
-    pub infectious_period_ticks: usize,
+    // Each person samples their own periods when they enter the exposed and infectious states.
+    // Periods are rounded to the nearest tick, and negative samples are clamped to 0.
+    // The infectious period is how long people are asymptomatic or symptomatic for, and does not
+    // include the presymptomatic period.
+    #[serde(deserialize_with = "deserialize_ticks_distribution")]
+    pub exposed_period_ticks: DistributionParams,
+    #[serde(deserialize_with = "deserialize_ticks_distribution")]
+    pub infectious_period_ticks: DistributionParams,
+
+    // If set, recovered people eventually lose their immunity (SEIRS).
+    // Otherwise, recovery is permanent (SEIR).
+    #[serde(default)]
+    pub waning_immunity: Option<WaningImmunityParams>,
+
+    // If set, infectious people are split into presymptomatic, asymptomatic and symptomatic.
+    // Otherwise, everyone is symptomatic as soon as they become infectious.
+    #[serde(default)]
+    pub symptoms: Option<SymptomParams>,
 
     pub spread_parameters: DiseaseSpreadParameters,
 }
 
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct CleaningParams {
+    // Stores are cleaned every this many ticks.
+    pub period_ticks: usize,
+
+    // Fraction of the contamination that each cleaning removes.
+    pub efficacy: f32,
+}
+
+// Surface contamination in stores.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct FomiteParams {
+    // Contamination that an infectious person leaves on their store cell each tick.
+    pub deposit_rate: f32,
+
+    // Multiplier on the contamination deposited by a person in each infectious state.
+    #[serde(default)]
+    pub deposit_multipliers: InfectiousStateValues,
+
+    // Fraction of a store cell's contamination that a person standing on it picks up each tick.
+    // The contamination that is picked up is removed from the surface.
+    pub pickup_fraction: f32,
+
+    // Probability per unit of contamination picked up that a susceptible person becomes
+    // infected.
+    pub infection_risk_per_unit: f32,
+
+    // Fraction of the contamination that deactivates each tick.
+    pub decay_rate: f32,
+
+    #[serde(default)]
+    pub cleaning: Option<CleaningParams>,
+}
+
+// Who shops for a household whose head of household is isolating, or that is quarantined.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum IsolationShopper {
+    // Nobody shops, and the household's supplies keep running down.
+    #[serde(rename = "nobody")]
+    Nobody,
+
+    // The lowest-indexed member of the household without symptoms shops alone.
+    #[serde(rename = "healthy_member")]
+    HealthyMember,
+}
+
 #[derive(Deserialize, Serialize, Debug, Copy, Clone)]
+pub struct SymptomaticIsolationParams {
+    // If set, the whole household quarantines for this many ticks after a member starts showing
+    // symptoms.
+    pub household_quarantine_ticks: Option<usize>,
+
+    pub shopper: IsolationShopper,
+}
+
+// How a household picks the store for each shopping trip.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Default)]
+pub enum StoreChoice {
+    // The store with the shortest path from the household.
+    #[default]
+    #[serde(rename = "nearest")]
+    Nearest,
+
+    // A store picked uniformly at random on each trip.
+    #[serde(rename = "random")]
+    Random,
+
+    // A store picked at random on each trip, weighted by the area of the store.
+    #[serde(rename = "weighted_by_size")]
+    WeightedBySize,
+
+    // The household always goes to the same store, picked at random when the world is created.
+    #[serde(rename = "home_store")]
+    HomeStore,
+}
+
+// Shoppers who may use a store during its special hours.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum SpecialHoursShoppers {
+    #[serde(rename = "seniors")]
+    Seniors,
+}
+
+// A window of the day during which only some households may go shopping.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SpecialHours {
+    pub start_time_of_day: usize,
+    pub end_time_of_day: usize,
+    pub shoppers: SpecialHoursShoppers,
+}
+
+// Times of day are in ticks since the start of the day, and windows run up to but not including
+// their end. A window that ends before it starts runs past midnight.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct OpeningHours {
+    pub open_time_of_day: usize,
+    pub close_time_of_day: usize,
+
+    // Special hours take precedence over the regular opening hours, and can be outside them.
+    #[serde(default)]
+    pub special_hours: Vec<SpecialHours>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ShopperParams {
     pub shopping_period_ticks: usize,
 
@@ -55,6 +512,29 @@ pub struct ShopperParams {
     pub supplies_bought_per_trip: f32,
 
     pub fraction_dual_shopper_households: f32,
+
+    // If set, people with symptoms stay home instead of going to the store. Requires symptom
+    // parameters.
+    #[serde(default)]
+    pub symptomatic_isolation: Option<SymptomaticIsolationParams>,
+
+    // Only matters on maps with more than one store.
+    #[serde(default)]
+    pub store_choice: StoreChoice,
+
+    // If set, at most this many shoppers can be in each store at once. Shoppers who arrive at a
+    // full store wait in a queue outside, and enter as others leave.
+    #[serde(default)]
+    pub max_store_occupancy: Option<usize>,
+
+    // If set, households only start shopping trips while the stores are open, so households that
+    // run out while the stores are closed wait for them to open. Requires a clock.
+    #[serde(default)]
+    pub opening_hours: Option<OpeningHours>,
+
+    // Fraction of households that count as seniors for special hours.
+    #[serde(default)]
+    pub fraction_senior_households: f32,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -74,6 +554,171 @@ pub struct MiscParams {
 
     // Fraction of people who have a n95 mask
     pub fraction_n95_mask: f32,
+
+    // Fractions of people who have each of the other kinds of mask
+    #[serde(default)]
+    pub fraction_cloth_mask: f32,
+    #[serde(default)]
+    pub fraction_surgical_mask: f32,
+    #[serde(default)]
+    pub fraction_respirator_mask: f32,
+
+    // If set, each person's mask fit is sampled from this, clamped to [0, 1]. A fit of 1 gets the
+    // mask's full filtration, and a fit of 0 gets no filtration at all. Otherwise, every mask fits
+    // perfectly.
+    #[serde(default)]
+    pub mask_fit: Option<DistributionParams>,
+
+    // How much of the viral particles each kind of mask lets through. Every spread model that
+    // accounts for masks uses this table.
+    #[serde(default)]
+    pub mask_filtration: MaskFiltrationTable,
+}
+
+// Fractions of people who have each kind of mask. The rest have no mask.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, Default)]
+pub struct MaskFractions {
+    pub fraction_mask: f32,
+    pub fraction_n95_mask: f32,
+    #[serde(default)]
+    pub fraction_cloth_mask: f32,
+    #[serde(default)]
+    pub fraction_surgical_mask: f32,
+    #[serde(default)]
+    pub fraction_respirator_mask: f32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub enum InterventionChange {
+    // Reassigns masks to everyone according to the new fractions. How well people's masks fit,
+    // and how much each kind of mask filters, stay as they were.
+    #[serde(rename = "set_mask_fractions")]
+    SetMaskFractions(MaskFractions),
+
+    // Only valid for shopper behavior.
+    #[serde(rename = "set_shopper_params")]
+    SetShopperParams(ShopperParams),
+
+    #[serde(rename = "set_spread_parameters")]
+    SetSpreadParameters(DiseaseSpreadParameters),
+
+    // Isolated households do not go shopping, even when they run out of supplies.
+    // Only valid for shopper behavior.
+    #[serde(rename = "set_household_isolation")]
+    SetHouseholdIsolation {
+        household_idxs: Vec<usize>,
+        isolated: bool,
+    },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ScheduledIntervention {
+    // The change takes effect at the start of the step that advances the world to this tick.
+    pub tick: usize,
+    pub change: InterventionChange,
+}
+
+// Population-level metrics that triggered policies react to.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone)]
+pub enum PolicyMetric {
+    #[serde(rename = "susceptible")]
+    Susceptible,
+    #[serde(rename = "exposed")]
+    Exposed,
+    // People in any of the infectious states.
+    #[serde(rename = "infectious")]
+    Infectious,
+    #[serde(rename = "symptomatic")]
+    Symptomatic,
+    #[serde(rename = "recovered")]
+    Recovered,
+
+    // Number of people who were exposed within the last window_ticks ticks.
+    #[serde(rename = "new_exposures")]
+    NewExposures { window_ticks: usize },
+}
+
+// A policy that turns on when a metric rises to the activation threshold, and turns off when it
+// falls below the deactivation threshold.
+// Setting the deactivation threshold below the activation threshold keeps the policy from
+// flapping on and off around a single threshold.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TriggeredPolicy {
+    pub metric: PolicyMetric,
+
+    // If true, the metric is divided by the number of people.
+    #[serde(default)]
+    pub as_fraction: bool,
+
+    pub activation_threshold: f32,
+    pub deactivation_threshold: f32,
+
+    pub on_activation: Vec<InterventionChange>,
+    pub on_deactivation: Vec<InterventionChange>,
+}
+
+// Attributes that vaccination campaigns can prioritize.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum VaccinationAttribute {
+    // Heads of household, who do the shopping for shopper behavior.
+    #[serde(rename = "head_of_household")]
+    HeadOfHousehold,
+
+    // People who do not have a mask when the world is created.
+    #[serde(rename = "no_mask")]
+    NoMask,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct VaccinationConfig {
+    // Length of a day, which the campaign's dose capacity is measured over.
+    pub ticks_per_day: usize,
+
+    // Times in the campaign are in ticks.
+    pub campaign: VaccinationParams<VaccinationAttribute>,
+}
+
+// People who comply turn away from anyone inside their personal space while they walk around
+// and while they shop. People following a path along the roads do not.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct DistancingParams {
+    pub personal_space_radius: f32,
+
+    // How hard people turn away, relative to their current heading. The repulsion from each
+    // neighbor grows linearly from 0 at the edge of the personal space to this at the center.
+    pub repulsion_strength: f32,
+
+    pub fraction_compliant: f32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct ElementSpeedMultiplier {
+    pub element: MapElement,
+    pub multiplier: f32,
+}
+
+// Without walking speed parameters, everyone walks 1 unit per tick, and 3 cells per tick along
+// roads.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct WalkingSpeedParams {
+    // Each person's speed, in units per tick, is drawn from this when the world is created. Every
+    // speed in the distribution must be positive, so that nobody gets stuck.
+    pub speed: DistributionParams,
+
+    // Multipliers on people's speed while they are on each kind of map element. Elements without
+    // a multiplier have a multiplier of 1.
+    #[serde(default)]
+    pub element_multipliers: Vec<ElementSpeedMultiplier>,
+}
+
+// Splits the ticks of a world into days.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct ClockParams {
+    pub ticks_per_day: usize,
+
+    // The time of day at tick 0, in ticks since the start of the day.
+    #[serde(default)]
+    pub start_time_of_day: usize,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -85,4 +730,59 @@ pub struct WorldConfig {
     pub num_initially_infected: usize,
     pub misc_parameters: MiscParams,
     pub map_params: Option<MapParams>,
+
+    #[serde(default)]
+    pub interventions: Vec<ScheduledIntervention>,
+
+    #[serde(default)]
+    pub policies: Vec<TriggeredPolicy>,
+
+    #[serde(default)]
+    pub vaccination: Option<VaccinationConfig>,
+
+    #[serde(default)]
+    pub clock: Option<ClockParams>,
+
+    #[serde(default)]
+    pub distancing: Option<DistancingParams>,
+
+    #[serde(default)]
+    pub walking_speed: Option<WalkingSpeedParams>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_disease_periods() -> serde_json::Result<()> {
+        let params: DiseaseParameters = serde_json::from_str(
+            r#"{
+                "exposed_period_ticks": 115,
+                "infectious_period_ticks": {"log_normal": [5.8, 0.2]},
+                "spread_parameters": {"infection_radius": 2.0}
+            }"#,
+        )?;
+
+        assert!(matches!(
+            params.exposed_period_ticks,
+            DistributionParams::Fixed(x) if x == 115.0
+        ));
+        assert!(matches!(
+            params.infectious_period_ticks,
+            DistributionParams::LogNormal(..)
+        ));
+        assert!(params.waning_immunity.is_none());
+        assert!(params.symptoms.is_none());
+
+        match params.spread_parameters {
+            DiseaseSpreadParameters::InfectionRadius(params) => {
+                assert_eq!(params.radius, 2.0);
+                assert_eq!(params.radius_multipliers, InfectiousStateValues::default());
+            }
+            _ => panic!("expected infection radius parameters"),
+        }
+
+        Ok(())
+    }
 }
@@ -1,35 +1,137 @@
 // Contains the core implementation of the v0 engine.
 
+use crate::v0::checkpoint::{SeededRng, SimulationRng, WorldCheckpoint};
 use crate::v0::config::*;
 use crate::v0::disease_spread::{
-    BackgroundViralParticleDiseaseSpreader, DiseaseSpreader, InfectionRadiusDiseaseSpreader,
+    new_disease_spreader, validate_spread_parameters, DiseaseSpreader,
+};
+use crate::v0::geometry::{closest_approach, Position};
+use crate::v0::interventions::{
+    schedule_interventions, validate_interventions, InterventionRecord,
 };
-use crate::v0::geometry::Position;
 use crate::v0::maps;
-use crate::v0::person_behavior::{BrownianMotionBehavior, PersonBehavior, ShopperBehavior};
+use crate::v0::person_behavior::{
+    validate_shopper_params, BrownianMotionBehavior, PersonBehavior, ShopperBehavior,
+};
+use crate::v0::transmission::{TransmissionEvent, TransmissionSource};
+use crate::v0::triggers::{validate_policies, PolicyActivation};
 use crate::v0::types::Mask;
 use crate::v0::utils::{random_bool_vec, random_vec};
-use anyhow::Result;
-use rand::RngCore;
+use crate::v0::vaccination::{new_vaccination_campaign, validate_vaccination};
+use crate::vaccination::VaccinationCampaign;
+use anyhow::{anyhow, Result};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Deserialize, Serialize)]
 pub(crate) enum DiseaseState {
     Susceptible,
-    Exposed(usize),    // Tick of when the person was exposed
-    Infectious(usize), // Tick of when the person was infected
-    Recovered,
+    Exposed(usize),        // Tick of when the person was exposed
+    Presymptomatic(usize), // Tick of when the person became infectious, before showing symptoms
+    Asymptomatic(usize), // Tick of when the person became infectious, without ever showing symptoms
+    Symptomatic(usize),  // Tick of when the person started showing symptoms
+    Recovered(usize),    // Tick of when the person recovered
+}
+
+impl DiseaseState {
+    pub(crate) fn is_infectious(&self) -> bool {
+        match self {
+            DiseaseState::Susceptible => false,
+            DiseaseState::Exposed(_) => false,
+            DiseaseState::Presymptomatic(_) => true,
+            DiseaseState::Asymptomatic(_) => true,
+            DiseaseState::Symptomatic(_) => true,
+            DiseaseState::Recovered(_) => false,
+        }
+    }
+}
+
+// Returns the state a person enters when they become infectious, along with how long they stay in
+// that state.
+fn start_infectious_period(
+    tick: usize,
+    params: &DiseaseParameters,
+    rng: &mut dyn RngCore,
+) -> (DiseaseState, usize) {
+    if let Some(symptoms) = &params.symptoms {
+        if rng.gen_bool(symptoms.fraction_asymptomatic as f64) {
+            return (
+                DiseaseState::Asymptomatic(tick),
+                params.infectious_period_ticks.sample_ticks(rng),
+            );
+        }
+
+        let presymptomatic_period_ticks = symptoms.presymptomatic_period_ticks.sample_ticks(rng);
+        if presymptomatic_period_ticks > 0 {
+            return (
+                DiseaseState::Presymptomatic(tick),
+                presymptomatic_period_ticks,
+            );
+        }
+    }
+
+    (
+        DiseaseState::Symptomatic(tick),
+        params.infectious_period_ticks.sample_ticks(rng),
+    )
 }
 
+#[derive(Clone, Deserialize, Serialize)]
 pub(crate) struct Person {
     pub(crate) id: usize,
     pub(crate) disease_state: DiseaseState,
     pub(crate) position: Position,
+    // Where the person was at the start of the tick. People are treated as having walked in a
+    // straight line from here to their position, so that contacts with people they walked past
+    // during the tick are not missed.
+    pub(crate) last_position: Position,
 
     // Index into the map's household for the person if the map exists.
     // Otherwise, 0.
     pub(crate) household_idx: usize,
     pub(crate) head_of_household: bool,
     pub(crate) mask: Mask,
+    // How well the person's mask fits, from 0 (no filtration) to 1 (the mask's full filtration).
+    pub(crate) mask_fit: f32,
+    // What the person's mask lets through, given how well it fits.
+    pub(crate) mask_filtration: MaskFiltration,
+
+    // Multiplier on the person's risk of being infected when exposed.
+    // This is reduced for people who were reinfected after losing their immunity.
+    pub(crate) susceptibility: f32,
+    // How long the person stays in their current disease state, sampled when they enter it.
+    // None if they stay in it indefinitely.
+    pub(crate) disease_period_ticks: Option<usize>,
+
+    // Tick of when the person was vaccinated, if they have been.
+    pub(crate) vaccinated_tick: Option<usize>,
+    // Multipliers from the person's vaccine on their risk of being infected and on how infectious
+    // they are. These fall from 1 as the vaccine takes effect.
+    pub(crate) vaccine_susceptibility: f32,
+    pub(crate) vaccine_infectiousness: f32,
+}
+
+impl Person {
+    // Multiplier on the person's risk of being infected when exposed.
+    pub(crate) fn get_susceptibility(&self) -> f32 {
+        self.susceptibility * self.vaccine_susceptibility
+    }
+
+    // Closest that the person got to the other person during the tick.
+    pub(crate) fn closest_distance(&self, other: &Person) -> f32 {
+        closest_approach(
+            &self.last_position,
+            &self.position,
+            &other.last_position,
+            &other.position,
+        )
+    }
+}
+
+impl ClockParams {
+    pub(crate) fn time_of_day(&self, tick: usize) -> usize {
+        (self.start_time_of_day + tick) % self.ticks_per_day
+    }
 }
 
 pub(crate) struct World {
@@ -38,16 +140,30 @@ pub(crate) struct World {
     pub(crate) tick: usize,
 
     pub(crate) people: Vec<Person>,
+    pub(crate) transmission_events: Vec<TransmissionEvent>,
+
+    // config.interventions sorted by tick. Interventions on the same tick keep their configured
+    // order.
+    pub(crate) scheduled_interventions: Vec<ScheduledIntervention>,
+    // Index into scheduled_interventions of the next intervention to apply.
+    pub(crate) next_intervention_idx: usize,
+    pub(crate) intervention_records: Vec<InterventionRecord>,
+
+    // Whether each of config.policies is currently active.
+    pub(crate) active_policies: Vec<bool>,
+    pub(crate) policy_activations: Vec<PolicyActivation>,
+
+    pub(crate) vaccination_campaign: Option<VaccinationCampaign>,
 
     // TODO: refactor for static dispatch
     pub(crate) disease_spreader: Box<dyn DiseaseSpreader>,
     pub(crate) person_behavior: Box<dyn PersonBehavior>,
 
-    rng: Box<dyn RngCore>,
+    pub(crate) rng: Box<dyn SimulationRng>,
 }
 
 impl World {
-    pub(crate) fn new(mut rng: Box<dyn RngCore>, config: WorldConfig) -> Result<Self> {
+    pub(crate) fn new(mut rng: Box<dyn SimulationRng>, config: WorldConfig) -> Result<Self> {
         // Load the map
         let maybe_map = if let Some(map_params) = &config.map_params {
             Some(maps::loader::load(map_params)?)
@@ -55,30 +171,83 @@ impl World {
             None
         };
 
+        validate_interventions(&config, maybe_map.as_ref())?;
+        validate_policies(&config, maybe_map.as_ref())?;
+        validate_vaccination(&config)?;
+        if let Some(clock) = &config.clock {
+            if clock.ticks_per_day == 0 || clock.start_time_of_day >= clock.ticks_per_day {
+                return Err(anyhow!("invalid clock parameters: {:?}", clock));
+            }
+        }
+        if let BehaviorParameters::Shopper(params) = &config.behavior_parameters {
+            validate_shopper_params(params, &config)?;
+        }
+        if let Some(params) = &config.walking_speed {
+            if !params.speed.is_valid()
+                || !params.speed.is_positive()
+                || params
+                    .element_multipliers
+                    .iter()
+                    .any(|element_multiplier| element_multiplier.multiplier <= 0.0)
+            {
+                return Err(anyhow!("invalid walking speed parameters: {:?}", params));
+            }
+        }
+        if let Some(params) = &config.distancing {
+            if !params.is_valid() {
+                return Err(anyhow!("invalid distancing parameters: {:?}", params));
+            }
+        }
+        if !config.misc_parameters.is_valid() {
+            return Err(anyhow!(
+                "invalid mask parameters: {:?}",
+                config.misc_parameters
+            ));
+        }
+        let disease_parameters = &config.disease_parameters;
+        if !disease_parameters.exposed_period_ticks.is_valid()
+            || !disease_parameters.infectious_period_ticks.is_valid()
+        {
+            return Err(anyhow!(
+                "invalid disease periods: {:?}",
+                config.disease_parameters
+            ));
+        }
+        if let Some(params) = &disease_parameters.symptoms {
+            if !params.presymptomatic_period_ticks.is_valid()
+                || !(0.0..=1.0).contains(&params.fraction_asymptomatic)
+            {
+                return Err(anyhow!("invalid symptom parameters: {:?}", params));
+            }
+        }
+        if let Some(params) = &disease_parameters.waning_immunity {
+            if !params.immunity_period_ticks.is_valid()
+                || !(0.0..=1.0).contains(&params.reinfection_susceptibility)
+            {
+                return Err(anyhow!("invalid waning immunity parameters: {:?}", params));
+            }
+        }
+        validate_spread_parameters(&disease_parameters.spread_parameters, maybe_map.as_ref())?;
         assert!(config.num_people >= config.num_initially_infected);
 
         let pct_initially_infected =
             config.num_initially_infected as f32 / config.num_people as f32;
         let infected_people = random_bool_vec(config.num_people, pct_initially_infected, &mut rng);
 
-        let masks = random_vec(
-            config.num_people,
-            Mask::Regular,
-            config.misc_parameters.fraction_mask,
-            Mask::N95,
-            config.misc_parameters.fraction_n95_mask,
-            Mask::None,
-            &mut rng,
-        );
+        let masks = Self::assign_masks(config.num_people, &config.misc_parameters, &mut rng);
+        let mask_fits =
+            Self::assign_mask_fits(config.num_people, &config.misc_parameters, &mut rng);
 
         let mut current_household_idx = 0;
         let mut people_in_current_household = 0;
         let people = (0..config.num_people)
             .map(|i| {
-                let disease_state = if infected_people[i] {
-                    DiseaseState::Infectious(0)
+                let (disease_state, disease_period_ticks) = if infected_people[i] {
+                    let (disease_state, period_ticks) =
+                        start_infectious_period(0, &config.disease_parameters, &mut rng);
+                    (disease_state, Some(period_ticks))
                 } else {
-                    DiseaseState::Susceptible
+                    (DiseaseState::Susceptible, None)
                 };
 
                 let bb = if let Some(map) = &maybe_map {
@@ -98,33 +267,54 @@ impl World {
                 Person {
                     id: i,
                     disease_state,
+                    last_position: position.clone(),
                     position,
                     household_idx: current_household_idx,
                     head_of_household: people_in_current_household == 1,
                     mask: masks[i],
+                    mask_fit: mask_fits[i],
+                    mask_filtration: config
+                        .misc_parameters
+                        .mask_filtration
+                        .get(masks[i], mask_fits[i]),
+                    susceptibility: 1.0,
+                    disease_period_ticks,
+                    vaccinated_tick: None,
+                    vaccine_susceptibility: 1.0,
+                    vaccine_infectiousness: 1.0,
                 }
             })
             .collect::<Vec<_>>();
 
-        let disease_spreader: Box<dyn DiseaseSpreader> =
-            match config.disease_parameters.spread_parameters {
-                DiseaseSpreadParameters::InfectionRadius(r) => {
-                    Box::new(InfectionRadiusDiseaseSpreader::new(r))
-                }
+        let transmission_events = people
+            .iter()
+            .filter(|p| infected_people[p.id])
+            .map(|p| TransmissionEvent {
+                tick: 0,
+                infectee: p.id,
+                source: TransmissionSource::InitiallyInfected,
+                location: Self::get_location(maybe_map.as_ref(), &p.position),
+                route: None,
+            })
+            .collect();
 
-                DiseaseSpreadParameters::BackgroundViralParticle(params) => Box::new(
-                    BackgroundViralParticleDiseaseSpreader::new(config.bounding_box, params),
-                ),
-            };
+        let disease_spreader = new_disease_spreader(
+            config.bounding_box,
+            &config.disease_parameters.spread_parameters,
+            maybe_map.as_ref(),
+        );
 
-        let person_behavior: Box<dyn PersonBehavior> = match config.behavior_parameters {
+        let person_behavior: Box<dyn PersonBehavior> = match &config.behavior_parameters {
             BehaviorParameters::BrownianMotion => Box::new(BrownianMotionBehavior::new(
                 config.bounding_box,
                 config.num_people,
+                config.distancing,
+                config.walking_speed.as_ref(),
                 &mut rng,
             )),
             BehaviorParameters::Shopper(params) => Box::new(ShopperBehavior::new(
-                params,
+                params.clone(),
+                &config,
                 &people,
                 maybe_map
                     .as_ref()
@@ -133,45 +323,396 @@ impl World {
             )),
         };
 
+        let active_policies = vec![false; config.policies.len()];
+        let scheduled_interventions = schedule_interventions(&config);
+
+        let vaccination_campaign = config
+            .vaccination
+            .as_ref()
+            .map(|params| new_vaccination_campaign(params, &people, &mut rng));
+
         Ok(World {
             config,
             map: maybe_map,
             tick: 0,
             people,
+            transmission_events,
+            scheduled_interventions,
+            next_intervention_idx: 0,
+            intervention_records: vec![],
+            active_policies,
+            policy_activations: vec![],
+            vaccination_campaign,
             disease_spreader,
             person_behavior,
             rng,
         })
     }
 
+    pub(crate) fn assign_masks(
+        num_people: usize,
+        params: &MiscParams,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Mask> {
+        random_vec(
+            num_people,
+            &[
+                (Mask::Regular, params.fraction_mask),
+                (Mask::N95, params.fraction_n95_mask),
+                (Mask::Cloth, params.fraction_cloth_mask),
+                (Mask::Surgical, params.fraction_surgical_mask),
+                (Mask::Respirator, params.fraction_respirator_mask),
+            ],
+            Mask::None,
+            rng,
+        )
+    }
+
+    // Everyone's mask fits perfectly unless the fit is configured to vary.
+    pub(crate) fn assign_mask_fits(
+        num_people: usize,
+        params: &MiscParams,
+        rng: &mut dyn RngCore,
+    ) -> Vec<f32> {
+        match &params.mask_fit {
+            Some(fit) => (0..num_people)
+                .map(|_| (fit.sample(rng) as f32).clamp(0.0, 1.0))
+                .collect(),
+            None => vec![1.0; num_people],
+        }
+    }
+
+    pub(crate) fn checkpoint(&self) -> Result<WorldCheckpoint> {
+        let rng_state = self
+            .rng
+            .get_state()
+            .ok_or_else(|| anyhow!("world rng does not support checkpointing"))?;
+
+        let path_cache = if let Some(map) = &self.map {
+            map.get_path_cache()
+        } else {
+            vec![]
+        };
+
+        Ok(WorldCheckpoint {
+            config: self.config.clone(),
+            tick: self.tick,
+            people: self.people.clone(),
+            transmission_events: self.transmission_events.clone(),
+            next_intervention_idx: self.next_intervention_idx,
+            intervention_records: self.intervention_records.clone(),
+            active_policies: self.active_policies.clone(),
+            policy_activations: self.policy_activations.clone(),
+            vaccination_campaign: self.vaccination_campaign.clone(),
+            disease_spreader: self.disease_spreader.checkpoint(),
+            person_behavior: self.person_behavior.checkpoint(),
+            path_cache,
+            rng_state,
+        })
+    }
+
+    pub(crate) fn from_checkpoint(checkpoint: WorldCheckpoint) -> Result<Self> {
+        let config = checkpoint.config;
+
+        // The map is fully determined by the config, except for the lazily computed paths.
+        let maybe_map = if let Some(map_params) = &config.map_params {
+            let map = maps::loader::load(map_params)?;
+            map.restore_path_cache(checkpoint.path_cache);
+            Some(map)
+        } else {
+            None
+        };
+
+        let scheduled_interventions = schedule_interventions(&config);
+
+        Ok(World {
+            config,
+            map: maybe_map,
+            tick: checkpoint.tick,
+            people: checkpoint.people,
+            transmission_events: checkpoint.transmission_events,
+            scheduled_interventions,
+            next_intervention_idx: checkpoint.next_intervention_idx,
+            intervention_records: checkpoint.intervention_records,
+            active_policies: checkpoint.active_policies,
+            policy_activations: checkpoint.policy_activations,
+            vaccination_campaign: checkpoint.vaccination_campaign,
+            disease_spreader: checkpoint.disease_spreader.restore(),
+            person_behavior: checkpoint.person_behavior.restore(),
+            rng: Box::new(SeededRng::from_state(&checkpoint.rng_state)),
+        })
+    }
+
+    // Ticks since the start of the current day, if the world has a clock.
+    pub(crate) fn time_of_day(&self) -> Option<usize> {
+        self.config.clock.map(|clock| clock.time_of_day(self.tick))
+    }
+
+    fn get_location(map: Option<&maps::Map>, position: &Position) -> Option<maps::MapElement> {
+        map.map(|m| m.get_element(position.y as usize, position.x as usize))
+    }
+
     pub fn step(&mut self) {
         self.tick += 1;
         let tick = self.tick;
 
+        // Step 0: apply any interventions that are scheduled for this tick, and vaccinate people.
+        self.apply_scheduled_interventions();
+        self.vaccinate();
+
         // Step 1: advance all the people
+        for p in self.people.iter_mut() {
+            p.last_position = p.position.clone();
+        }
         self.person_behavior
-            .update_positions(&mut self.people, &mut self.map, &mut self.rng);
+            .update_positions(tick, &mut self.people, &mut self.map, &mut self.rng);
 
-        // Step 2: Update disease state according to the spread model.
-        self.disease_spreader
+        // Step 2: Update disease state according to the spread model, and record who was
+        // exposed.
+        let exposures = self
+            .disease_spreader
             .spread(tick, &mut self.rng, &mut self.people);
+        let exposed_period_ticks = self.config.disease_parameters.exposed_period_ticks;
+        for exposure in exposures.into_iter() {
+            self.people[exposure.infectee].disease_period_ticks =
+                Some(exposed_period_ticks.sample_ticks(&mut self.rng));
+
+            let position = &self.people[exposure.infectee].position;
+            let location = if self.person_behavior.is_queueing(exposure.infectee) {
+                Some(maps::MapElement::StoreQueue)
+            } else {
+                Self::get_location(self.map.as_ref(), position)
+            };
+            self.transmission_events.push(TransmissionEvent {
+                tick,
+                infectee: exposure.infectee,
+                source: exposure.source,
+                location,
+                route: exposure.route,
+            });
+        }
 
         // Step 3: Update time-based disease states:
         //   * Advance exposed states to infectious
-        //   * Advance infectious states to recovered
+        //   * Advance presymptomatic states to symptomatic
+        //   * Advance asymptomatic and symptomatic states to recovered
+        //   * Advance recovered states back to susceptible, if immunity wanes
         let disease_parameters = &self.config.disease_parameters;
-        self.people.iter_mut().for_each(|p| match p.disease_state {
-            DiseaseState::Exposed(start_tick) => {
-                if tick - start_tick >= disease_parameters.exposed_period_ticks {
-                    p.disease_state = DiseaseState::Infectious(tick);
-                }
+        let rng = &mut self.rng;
+        for p in self.people.iter_mut() {
+            let start_tick = match p.disease_state {
+                DiseaseState::Exposed(start_tick)
+                | DiseaseState::Presymptomatic(start_tick)
+                | DiseaseState::Asymptomatic(start_tick)
+                | DiseaseState::Symptomatic(start_tick)
+                | DiseaseState::Recovered(start_tick) => start_tick,
+                DiseaseState::Susceptible => continue,
+            };
+            match p.disease_period_ticks {
+                Some(period_ticks) if tick - start_tick >= period_ticks => {}
+                _ => continue,
             }
-            DiseaseState::Infectious(start_tick) => {
-                if tick - start_tick >= disease_parameters.infectious_period_ticks {
-                    p.disease_state = DiseaseState::Recovered;
+
+            match p.disease_state {
+                DiseaseState::Exposed(_) => {
+                    let (disease_state, period_ticks) =
+                        start_infectious_period(tick, disease_parameters, &mut *rng);
+                    p.disease_state = disease_state;
+                    p.disease_period_ticks = Some(period_ticks);
+                }
+                DiseaseState::Presymptomatic(_) => {
+                    p.disease_state = DiseaseState::Symptomatic(tick);
+                    p.disease_period_ticks = Some(
+                        disease_parameters
+                            .infectious_period_ticks
+                            .sample_ticks(&mut *rng),
+                    );
+                }
+                DiseaseState::Asymptomatic(_) | DiseaseState::Symptomatic(_) => {
+                    p.disease_state = DiseaseState::Recovered(tick);
+                    p.disease_period_ticks = disease_parameters
+                        .waning_immunity
+                        .map(|params| params.immunity_period_ticks.sample_ticks(&mut *rng));
                 }
+                DiseaseState::Recovered(_) => {
+                    // Recovered people only have a period if their immunity wanes.
+                    if let Some(params) = &disease_parameters.waning_immunity {
+                        p.disease_state = DiseaseState::Susceptible;
+                        p.susceptibility = params.reinfection_susceptibility;
+                    }
+                    p.disease_period_ticks = None;
+                }
+                DiseaseState::Susceptible => {}
             }
-            DiseaseState::Susceptible | DiseaseState::Recovered => {}
+        }
+
+        // Step 4: switch policies on or off based on the updated disease states.
+        self.update_triggered_policies();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::city::config::DistributionParams;
+    use crate::v0::geometry::BoundingBox;
+    use crate::v0::test_util;
+    use crate::v0::transmission::TransmissionTree;
+
+    fn waning_immunity_config() -> WorldConfig {
+        let mut config = test_util::brownian_motion_config(1.0);
+        config.disease_parameters.exposed_period_ticks = DistributionParams::Fixed(0.0);
+        config.disease_parameters.infectious_period_ticks = DistributionParams::Fixed(3.0);
+        config.disease_parameters.waning_immunity = Some(WaningImmunityParams {
+            immunity_period_ticks: DistributionParams::Fixed(5.0),
+            reinfection_susceptibility: 0.5,
+        });
+        config.num_people = 10;
+        config
+    }
+
+    #[test]
+    fn test_immunity_wanes() -> Result<()> {
+        let mut world = World::new(Box::new(SeededRng::new(10914)), waning_immunity_config())?;
+
+        for _ in 0..3 {
+            world.step();
+        }
+        assert!(world
+            .people
+            .iter()
+            .all(|p| p.disease_state == DiseaseState::Recovered(3)));
+
+        for _ in 0..4 {
+            world.step();
+        }
+        assert!(world
+            .people
+            .iter()
+            .all(|p| p.disease_state == DiseaseState::Recovered(3)));
+
+        world.step();
+        assert!(world
+            .people
+            .iter()
+            .all(|p| p.disease_state == DiseaseState::Susceptible && p.susceptibility == 0.5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generation_intervals_with_reinfections() -> Result<()> {
+        let mut config = waning_immunity_config();
+        config.num_people = 100;
+        config.num_initially_infected = 10;
+        config.bounding_box = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 20,
+            right: 20,
+        };
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+        for _ in 0..100 {
+            world.step();
+        }
+
+        let tree = TransmissionTree::from_events(&world.transmission_events);
+        // Some infections were caused by an infector who had been infected before.
+        assert!(tree.nodes().any(|node| match node.parent {
+            Some(parent) => tree.infections(tree.get(parent).unwrap().person).count() > 1,
+            None => false,
+        }));
+        // Everyone is infectious for 3 ticks after being infected.
+        let intervals = tree.generation_intervals();
+        assert!(!intervals.is_empty());
+        assert!(intervals.iter().all(|interval| *interval <= 3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_infectious_periods_vary_per_person() -> Result<()> {
+        let mut config = waning_immunity_config();
+        config.num_people = 100;
+        config.num_initially_infected = 100;
+        config.disease_parameters.infectious_period_ticks = DistributionParams::Uniform(5.0, 15.0);
+        config.disease_parameters.waning_immunity = None;
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+
+        for _ in 0..15 {
+            world.step();
+        }
+
+        let recovered_ticks = world
+            .people
+            .iter()
+            .map(|p| match p.disease_state {
+                DiseaseState::Recovered(tick) => tick,
+                _ => panic!("everyone should have recovered"),
+            })
+            .collect::<Vec<_>>();
+        assert!(recovered_ticks.iter().all(|tick| (5..=15).contains(tick)));
+        assert!(recovered_ticks.contains(&5) && recovered_ticks.contains(&15));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_symptomatic_disease_course() -> Result<()> {
+        let mut config = waning_immunity_config();
+        config.disease_parameters.waning_immunity = None;
+        config.disease_parameters.symptoms = Some(SymptomParams {
+            fraction_asymptomatic: 0.0,
+            presymptomatic_period_ticks: DistributionParams::Fixed(2.0),
+        });
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config.clone())?;
+
+        let mut states = vec![world.people[0].disease_state.clone()];
+        for _ in 0..5 {
+            world.step();
+            states.push(world.people[0].disease_state.clone());
+        }
+        assert_eq!(
+            states,
+            vec![
+                DiseaseState::Presymptomatic(0),
+                DiseaseState::Presymptomatic(0),
+                DiseaseState::Symptomatic(2),
+                DiseaseState::Symptomatic(2),
+                DiseaseState::Symptomatic(2),
+                DiseaseState::Recovered(5),
+            ]
+        );
+
+        config.disease_parameters.symptoms = Some(SymptomParams {
+            fraction_asymptomatic: 1.0,
+            presymptomatic_period_ticks: DistributionParams::Fixed(2.0),
         });
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+        assert!(world
+            .people
+            .iter()
+            .all(|p| p.disease_state == DiseaseState::Asymptomatic(0)));
+
+        for _ in 0..3 {
+            world.step();
+        }
+        assert!(world
+            .people
+            .iter()
+            .all(|p| p.disease_state == DiseaseState::Recovered(3)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_waning_immunity() {
+        let mut config = waning_immunity_config();
+        config.disease_parameters.waning_immunity = Some(WaningImmunityParams {
+            immunity_period_ticks: DistributionParams::Fixed(5.0),
+            reinfection_susceptibility: 1.5,
+        });
+
+        assert!(World::new(Box::new(SeededRng::new(10914)), config).is_err());
     }
 }
@@ -1,66 +1,566 @@
 // Utilities for simulating the spread of disease.
 
 use crate::rand::Rng;
-use crate::v0::config::BackgroundViralParticleParams;
+use crate::v0::config::{
+    AirMovementParams, BackgroundViralParticleParams, DiseaseSpreadParameters, DoseModelParams,
+    DoseResponse, ExhaleKernel, FomiteParams, HouseholdTransmissionParams, InfectionModel,
+    InfectionRadiusParams, InfectiousStateValues, MaskFiltration, MaskFiltrationTable,
+    ProbabilisticRadiusParams, TransmissionCurve, VentilationParams, VentilationTarget,
+};
 use crate::v0::core::{DiseaseState, Person};
-use crate::v0::geometry::{BoundingBox, Position};
+use crate::v0::geometry::{normalize_angle, BoundingBox, Position};
+use crate::v0::maps::{Map, MapElement};
+use crate::v0::spatial_index::UniformGrid;
+use crate::v0::transmission::{Exposure, TransmissionSource};
 use crate::v0::types::Mask;
+use anyhow::{anyhow, Result};
 use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
 
 pub(crate) trait DiseaseSpreader {
-    fn spread(&mut self, tick: usize, rng: &mut dyn RngCore, people: &mut [Person]);
+    // Returns the people who were exposed during this tick.
+    fn spread(
+        &mut self,
+        tick: usize,
+        rng: &mut dyn RngCore,
+        people: &mut [Person],
+    ) -> Vec<Exposure>;
 
-    fn get_background_viral_levels(&self) -> &Vec<f32> {
-        unimplemented!()
+    fn checkpoint(&self) -> DiseaseSpreaderCheckpoint;
+
+    // Updates the parameters of the spread model in place.
+    // Returns false if the parameters are for a different spread model.
+    fn set_parameters(&mut self, params: &DiseaseSpreadParameters, map: Option<&Map>) -> bool;
+
+    // Returns the particles in each cell, if the spread model has background viral particles.
+    fn get_background_viral_levels(&self) -> Option<&Vec<f32>> {
+        None
+    }
+}
+
+pub(crate) fn new_disease_spreader(
+    world_bb: BoundingBox,
+    params: &DiseaseSpreadParameters,
+    map: Option<&Map>,
+) -> Box<dyn DiseaseSpreader> {
+    match params {
+        DiseaseSpreadParameters::InfectionRadius(params) => {
+            Box::new(InfectionRadiusDiseaseSpreader::new(world_bb, *params))
+        }
+
+        DiseaseSpreadParameters::BackgroundViralParticle(params) => Box::new(
+            BackgroundViralParticleDiseaseSpreader::new(world_bb, params.clone(), map),
+        ),
+
+        DiseaseSpreadParameters::ProbabilisticRadius(params) => Box::new(
+            ProbabilisticRadiusDiseaseSpreader::new(world_bb, params.clone()),
+        ),
+
+        DiseaseSpreadParameters::Household(params) => {
+            Box::new(HouseholdDiseaseSpreader::new(*params, map))
+        }
+
+        DiseaseSpreadParameters::Fomite(params) => {
+            Box::new(FomiteDiseaseSpreader::new(world_bb, *params, map))
+        }
+
+        DiseaseSpreadParameters::Composite(routes) => Box::new(CompositeDiseaseSpreader {
+            spreaders: routes
+                .iter()
+                .map(|params| new_disease_spreader(world_bb, params, map))
+                .collect(),
+        }),
+    }
+}
+
+// Saved state of a disease spreader, used to restore it from a checkpoint.
+#[derive(Deserialize, Serialize)]
+pub(crate) enum DiseaseSpreaderCheckpoint {
+    InfectionRadius(InfectionRadiusDiseaseSpreader),
+    BackgroundViralParticle(Box<BackgroundViralParticleDiseaseSpreader>),
+    ProbabilisticRadius(ProbabilisticRadiusDiseaseSpreader),
+    Fomite(FomiteDiseaseSpreader),
+    Household(HouseholdDiseaseSpreader),
+    Composite(Vec<DiseaseSpreaderCheckpoint>),
+}
+
+impl DiseaseSpreaderCheckpoint {
+    pub(crate) fn restore(self) -> Box<dyn DiseaseSpreader> {
+        match self {
+            DiseaseSpreaderCheckpoint::InfectionRadius(spreader) => Box::new(spreader),
+            DiseaseSpreaderCheckpoint::BackgroundViralParticle(spreader) => spreader,
+            DiseaseSpreaderCheckpoint::ProbabilisticRadius(spreader) => Box::new(spreader),
+            DiseaseSpreaderCheckpoint::Fomite(spreader) => Box::new(spreader),
+            DiseaseSpreaderCheckpoint::Household(spreader) => Box::new(spreader),
+            DiseaseSpreaderCheckpoint::Composite(checkpoints) => {
+                Box::new(CompositeDiseaseSpreader {
+                    spreaders: checkpoints
+                        .into_iter()
+                        .map(|checkpoint| checkpoint.restore())
+                        .collect(),
+                })
+            }
+        }
+    }
+}
+
+impl InfectiousStateValues {
+    // Returns the value for the disease state, or None if the state is not infectious.
+    pub(crate) fn get(&self, disease_state: &DiseaseState) -> Option<f32> {
+        match disease_state {
+            DiseaseState::Presymptomatic(_) => Some(self.presymptomatic),
+            DiseaseState::Asymptomatic(_) => Some(self.asymptomatic),
+            DiseaseState::Symptomatic(_) => Some(self.symptomatic),
+            DiseaseState::Susceptible | DiseaseState::Exposed(_) | DiseaseState::Recovered(_) => {
+                None
+            }
+        }
+    }
+
+    fn max(&self) -> f32 {
+        self.presymptomatic
+            .max(self.asymptomatic)
+            .max(self.symptomatic)
+    }
+}
+
+impl MaskFiltration {
+    fn is_valid(&self) -> bool {
+        (0.0..=1.0).contains(&self.inhale) && (0.0..=1.0).contains(&self.exhale)
+    }
+}
+
+impl MaskFiltrationTable {
+    // Returns the filtration of the mask for someone whose mask fits this well.
+    // Particles that the mask would have blocked leak through in proportion to how poorly it fits.
+    pub(crate) fn get(&self, mask: Mask, fit: f32) -> MaskFiltration {
+        let filtration = match mask {
+            Mask::None => self.none,
+            Mask::Regular => self.regular,
+            Mask::N95 => self.n95,
+            Mask::Cloth => self.cloth,
+            Mask::Surgical => self.surgical,
+            Mask::Respirator => self.respirator,
+        };
+        if fit >= 1.0 {
+            return filtration;
+        }
+
+        MaskFiltration {
+            inhale: 1.0 - fit * (1.0 - filtration.inhale),
+            exhale: 1.0 - fit * (1.0 - filtration.exhale),
+        }
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        [
+            self.none,
+            self.regular,
+            self.n95,
+            self.cloth,
+            self.surgical,
+            self.respirator,
+        ]
+        .iter()
+        .all(|filtration| filtration.is_valid())
+    }
+}
+
+impl ExhaleKernel {
+    fn is_valid(&self) -> bool {
+        match self {
+            ExhaleKernel::Uniform => true,
+            ExhaleKernel::Gaussian { stddev } => *stddev > 0.0,
+            ExhaleKernel::InverseSquare { min_distance } => *min_distance > 0.0,
+            ExhaleKernel::Cone { half_angle_rad } => *half_angle_rad > 0.0 && *half_angle_rad <= PI,
+        }
+    }
+}
+
+impl TransmissionCurve {
+    fn is_valid(&self) -> bool {
+        let is_probability = |p: &f32| (0.0..=1.0).contains(p);
+        match self {
+            TransmissionCurve::Exponential { p0, length_scale } => {
+                is_probability(p0) && *length_scale > 0.0
+            }
+            TransmissionCurve::Logistic { p_max, .. } => is_probability(p_max),
+            TransmissionCurve::Table(points) => {
+                !points.is_empty()
+                    && points.iter().all(|(_, p)| is_probability(p))
+                    && points.windows(2).all(|w| w[0].0 < w[1].0)
+            }
+        }
+    }
+
+    fn probability(&self, distance: f32) -> f32 {
+        match self {
+            TransmissionCurve::Exponential { p0, length_scale } => {
+                p0 * (-distance / length_scale).exp()
+            }
+            TransmissionCurve::Logistic {
+                p_max,
+                midpoint,
+                steepness,
+            } => p_max / (1.0 + (steepness * (distance - midpoint)).exp()),
+            TransmissionCurve::Table(points) => {
+                let next_idx = points.iter().position(|(d, _)| *d > distance);
+                match next_idx {
+                    Some(0) => points[0].1,
+                    Some(idx) => {
+                        let (d0, p0) = points[idx - 1];
+                        let (d1, p1) = points[idx];
+                        p0 + (p1 - p0) * (distance - d0) / (d1 - d0)
+                    }
+                    None => points[points.len() - 1].1,
+                }
+            }
+        }
+    }
+}
+
+impl DoseResponse {
+    fn is_valid(&self) -> bool {
+        match self {
+            DoseResponse::Exponential { k } => *k > 0.0,
+            DoseResponse::BetaPoisson { alpha, n50 } => *alpha > 0.0 && *n50 > 0.0,
+        }
+    }
+
+    // Probability that a person who has inhaled this dose is infected.
+    fn probability(&self, dose: f32) -> f32 {
+        match self {
+            DoseResponse::Exponential { k } => 1.0 - (-dose / k).exp(),
+            DoseResponse::BetaPoisson { alpha, n50 } => {
+                1.0 - (1.0 + dose * (2f32.powf(1.0 / alpha) - 1.0) / n50).powf(-alpha)
+            }
+        }
+    }
+}
+
+impl DoseModelParams {
+    fn is_valid(&self) -> bool {
+        self.breathing_rate >= 0.0
+            && (0.0..=1.0).contains(&self.dose_decay_rate)
+            && self.dose_response.is_valid()
+    }
+
+    // Probability of being infected this tick when the accumulated dose goes from prev_dose to
+    // dose, given that the person was not infected by prev_dose.
+    fn infection_probability(&self, prev_dose: f32, dose: f32) -> f32 {
+        let prev_probability = self.dose_response.probability(prev_dose);
+        let probability = self.dose_response.probability(dose);
+        if prev_probability >= 1.0 {
+            return 1.0;
+        }
+        ((probability - prev_probability) / (1.0 - prev_probability)).max(0.0)
+    }
+}
+
+impl AirMovementParams {
+    fn is_valid(&self) -> bool {
+        (0.0..=0.25).contains(&self.diffusion_coefficient)
+            && self.wind_x.abs() + self.wind_y.abs() <= 1.0
+    }
+}
+
+impl VentilationParams {
+    fn is_valid(&self, map: Option<&Map>) -> bool {
+        let target_exists = match self.target {
+            VentilationTarget::MapElement(_) => true,
+            VentilationTarget::Household(idx) => idx < map.map_or(0, |m| m.households.len()),
+            VentilationTarget::Store(idx) => idx < map.map_or(0, |m| m.stores.len()),
+        };
+
+        target_exists
+            && (0.0..=1.0).contains(&self.decay_rate)
+            && (0.0..=1.0).contains(&self.air_exchange_rate)
+    }
+}
+
+impl DiseaseSpreadParameters {
+    fn is_valid(&self, map: Option<&Map>) -> bool {
+        match self {
+            DiseaseSpreadParameters::InfectionRadius(_) => true,
+            DiseaseSpreadParameters::BackgroundViralParticle(params) => {
+                params.exhale_kernel.is_valid()
+                    && params.air_movement.iter().all(AirMovementParams::is_valid)
+                    && params.ventilation.iter().all(|v| v.is_valid(map))
+                    && match params.infection_model {
+                        InfectionModel::PerTick => true,
+                        InfectionModel::Dose(dose_params) => dose_params.is_valid(),
+                    }
+            }
+            DiseaseSpreadParameters::ProbabilisticRadius(params) => {
+                params.max_distance >= 0.0 && params.curve.is_valid()
+            }
+            DiseaseSpreadParameters::Household(params) => {
+                map.is_some() && (0.0..=1.0).contains(&params.hazard_per_tick)
+            }
+            DiseaseSpreadParameters::Fomite(params) => params.is_valid(),
+            DiseaseSpreadParameters::Composite(routes) => {
+                !routes.is_empty() && routes.iter().all(|params| params.is_valid(map))
+            }
+        }
+    }
+}
+
+impl FomiteParams {
+    fn is_valid(&self) -> bool {
+        self.deposit_rate >= 0.0
+            && (0.0..=1.0).contains(&self.pickup_fraction)
+            && self.infection_risk_per_unit >= 0.0
+            && (0.0..=1.0).contains(&self.decay_rate)
+            && self.cleaning.iter().all(|cleaning| {
+                cleaning.period_ticks > 0 && (0.0..=1.0).contains(&cleaning.efficacy)
+            })
     }
 }
 
+pub(crate) fn validate_spread_parameters(
+    params: &DiseaseSpreadParameters,
+    map: Option<&Map>,
+) -> Result<()> {
+    if !params.is_valid(map) {
+        return Err(anyhow!("invalid spread parameters: {:?}", params));
+    }
+
+    Ok(())
+}
+
+// Radius to search around a person's position for people who came within the given distance of
+// them during the tick. Both of them may have walked up to the furthest that anyone walked.
+fn get_swept_radius(people: &[Person], distance: f32) -> f32 {
+    let max_step = people
+        .iter()
+        .map(|p| p.last_position.distance(&p.position))
+        .fold(0.0, f32::max);
+    distance + 2.0 * max_step
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 // Infection Radius Spread
 ///////////////////////////////////////////////////////////////////////////////
 
+#[derive(Clone, Deserialize, Serialize)]
 pub(crate) struct InfectionRadiusDiseaseSpreader {
-    radius: f32,
+    world_bounding_box: BoundingBox,
+    params: InfectionRadiusParams,
 }
 
 impl InfectionRadiusDiseaseSpreader {
-    pub(crate) fn new(radius: f32) -> Self {
-        InfectionRadiusDiseaseSpreader { radius }
+    pub(crate) fn new(world_bb: BoundingBox, params: InfectionRadiusParams) -> Self {
+        InfectionRadiusDiseaseSpreader {
+            world_bounding_box: world_bb,
+            params,
+        }
+    }
+
+    // Returns the radius around the person in which they expose others, or None if they are not
+    // infectious.
+    fn get_radius(&self, person: &Person) -> Option<f32> {
+        let multiplier = self.params.radius_multipliers.get(&person.disease_state)?;
+        Some(self.params.radius * multiplier)
     }
 }
 
 impl DiseaseSpreader for InfectionRadiusDiseaseSpreader {
-    fn spread(&mut self, tick: usize, _: &mut dyn RngCore, people: &mut [Person]) {
-        // TODO: instead of a N^2 loop, use some index structure (BTreeMap?)
-        for i in 0..(people.len() - 1) {
-            let (left, right) = people.split_at_mut(i + 1);
-            let p0 = left.last_mut().unwrap();
-            let p0_position = &p0.position;
+    fn spread(
+        &mut self,
+        tick: usize,
+        rng: &mut dyn RngCore,
+        people: &mut [Person],
+    ) -> Vec<Exposure> {
+        let max_radius = self.params.radius * self.params.radius_multipliers.max();
+        if max_radius <= 0.0 {
+            return vec![];
+        }
 
-            for p1 in right.iter_mut() {
-                let p1_position = &p1.position;
+        // Index the infectious people, so that each susceptible person only needs to look at the
+        // infectious people in the cells around them.
+        let infectious_people = UniformGrid::new(
+            &self.world_bounding_box,
+            max_radius,
+            people
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.disease_state.is_infectious())
+                .map(|(idx, p)| (idx, &p.position)),
+        );
 
-                if p0_position.distance(p1_position) >= self.radius {
-                    continue;
+        // A susceptible person is exposed by the lowest-indexed infectious person whose radius
+        // they came into during the tick. This matches comparing every pair of people in index
+        // order.
+        let search_radius = get_swept_radius(people, max_radius);
+        let mut exposures = people
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.disease_state == DiseaseState::Susceptible)
+            .filter_map(|(idx, p)| {
+                let infector_idx = infectious_people
+                    .query(&p.position, search_radius)
+                    .filter(|other_idx| {
+                        let other = &people[*other_idx];
+                        let radius = self.get_radius(other).unwrap_or(0.0);
+                        p.closest_distance(other) < radius
+                    })
+                    .min()?;
+                Some((idx, infector_idx))
+            })
+            .collect::<Vec<_>>();
+
+        // Report the exposures in the order that the pairs would have been compared.
+        exposures
+            .sort_by_key(|(idx, infector_idx)| (*idx.min(infector_idx), *idx.max(infector_idx)));
+
+        exposures
+            .into_iter()
+            .filter_map(|(idx, infector_idx)| {
+                // Exposures can only be escaped when the susceptible person or the infector is
+                // protected, so that the RNG is left untouched when nobody has been reinfected or
+                // vaccinated.
+                let p_infect =
+                    people[idx].get_susceptibility() * people[infector_idx].vaccine_infectiousness;
+                if p_infect < 1.0 && !rng.gen_bool(p_infect as f64) {
+                    return None;
                 }
 
-                match (&p0.disease_state, &p1.disease_state) {
-                    // If one person is susceptible and the other is infectious, expose the
-                    // susceptible person.
-                    (DiseaseState::Susceptible, DiseaseState::Infectious(_)) => {
-                        p0.disease_state = DiseaseState::Exposed(tick);
-                    }
-                    (DiseaseState::Infectious(_), DiseaseState::Susceptible) => {
-                        p1.disease_state = DiseaseState::Exposed(tick);
-                    }
-                    // Otherwise, no-op.
-                    (DiseaseState::Susceptible, DiseaseState::Susceptible) => (),
-                    (DiseaseState::Recovered, _) | (_, DiseaseState::Recovered) => (),
-                    (DiseaseState::Exposed(_), _) | (_, DiseaseState::Exposed(_)) => (),
-                    (DiseaseState::Infectious(_), DiseaseState::Infectious(_)) => (),
-                };
+                people[idx].disease_state = DiseaseState::Exposed(tick);
+                Some(Exposure {
+                    infectee: people[idx].id,
+                    source: TransmissionSource::CloseContact {
+                        infector: people[infector_idx].id,
+                    },
+                    route: None,
+                })
+            })
+            .collect()
+    }
+
+    fn checkpoint(&self) -> DiseaseSpreaderCheckpoint {
+        DiseaseSpreaderCheckpoint::InfectionRadius(self.clone())
+    }
+
+    fn set_parameters(&mut self, params: &DiseaseSpreadParameters, _map: Option<&Map>) -> bool {
+        if let DiseaseSpreadParameters::InfectionRadius(params) = params {
+            self.params = *params;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Probabilistic Radius Spread
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Deserialize, Serialize)]
+pub(crate) struct ProbabilisticRadiusDiseaseSpreader {
+    world_bounding_box: BoundingBox,
+    params: ProbabilisticRadiusParams,
+}
+
+impl ProbabilisticRadiusDiseaseSpreader {
+    pub(crate) fn new(world_bb: BoundingBox, params: ProbabilisticRadiusParams) -> Self {
+        ProbabilisticRadiusDiseaseSpreader {
+            world_bounding_box: world_bb,
+            params,
+        }
+    }
+
+    // Probability that the infector exposes the susceptible person during this tick.
+    fn get_probability(&self, susceptible: &Person, infector: &Person) -> f32 {
+        let multiplier = match self
+            .params
+            .infectiousness_multipliers
+            .get(&infector.disease_state)
+        {
+            Some(multiplier) => multiplier,
+            None => return 0.0,
+        };
+        let distance = susceptible.closest_distance(infector);
+        if distance > self.params.max_distance {
+            return 0.0;
+        }
+
+        // Masks reduce the probability by the fraction of particles that get through the infectious
+        // person's mask on the way out, and the susceptible person's on the way in.
+        self.params.curve.probability(distance)
+            * multiplier
+            * susceptible.mask_filtration.inhale
+            * infector.mask_filtration.exhale
+            * susceptible.get_susceptibility()
+            * infector.vaccine_infectiousness
+    }
+}
+
+impl DiseaseSpreader for ProbabilisticRadiusDiseaseSpreader {
+    fn spread(
+        &mut self,
+        tick: usize,
+        rng: &mut dyn RngCore,
+        people: &mut [Person],
+    ) -> Vec<Exposure> {
+        if self.params.max_distance <= 0.0 {
+            return vec![];
+        }
+
+        let infectious_people = UniformGrid::new(
+            &self.world_bounding_box,
+            self.params.max_distance,
+            people
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.disease_state.is_infectious())
+                .map(|(idx, p)| (idx, &p.position)),
+        );
+
+        // Each infectious person who came nearby during the tick gets an independent chance to
+        // expose a susceptible person, in index order, until one of them succeeds.
+        let search_radius = get_swept_radius(people, self.params.max_distance);
+        let mut exposures = vec![];
+        for idx in 0..people.len() {
+            if people[idx].disease_state != DiseaseState::Susceptible {
+                continue;
+            }
+
+            let mut infector_idxs = infectious_people
+                .query(&people[idx].position, search_radius)
+                .collect::<Vec<_>>();
+            infector_idxs.sort_unstable();
+
+            let infector_idx = infector_idxs.into_iter().find(|infector_idx| {
+                let p_infect = self.get_probability(&people[idx], &people[*infector_idx]);
+                p_infect > 0.0 && rng.gen_bool(p_infect.min(1.0) as f64)
+            });
+            if let Some(infector_idx) = infector_idx {
+                people[idx].disease_state = DiseaseState::Exposed(tick);
+                exposures.push(Exposure {
+                    infectee: people[idx].id,
+                    source: TransmissionSource::CloseContact {
+                        infector: people[infector_idx].id,
+                    },
+                    route: None,
+                });
             }
         }
+
+        exposures
+    }
+
+    fn checkpoint(&self) -> DiseaseSpreaderCheckpoint {
+        DiseaseSpreaderCheckpoint::ProbabilisticRadius(self.clone())
+    }
+
+    fn set_parameters(&mut self, params: &DiseaseSpreadParameters, _map: Option<&Map>) -> bool {
+        if let DiseaseSpreadParameters::ProbabilisticRadius(params) = params {
+            self.params = params.clone();
+            true
+        } else {
+            false
+        }
     }
 }
 
@@ -68,34 +568,330 @@ impl DiseaseSpreader for InfectionRadiusDiseaseSpreader {
 // Background Viral Particle Spread
 ///////////////////////////////////////////////////////////////////////////////
 
+#[derive(Clone, Deserialize, Serialize)]
 pub(crate) struct BackgroundViralParticleDiseaseSpreader {
     world_bounding_box: BoundingBox,
     params: BackgroundViralParticleParams,
     background_viral_particles: Vec<f32>,
+    // Fraction of the particles in each cell that survive each tick, if any ventilation rules are
+    // set. Otherwise, this is empty and every cell uses the global decay rate.
+    survival_rates: Vec<f32>,
+    // Flags for which cells are walls and which of their edges block particles, if walls are
+    // enabled. Otherwise, this is empty and particles move freely.
+    barriers: Vec<u8>,
+    // Scratch space for moving particles between cells, kept around to avoid reallocating it
+    // every tick.
+    #[serde(skip)]
+    next_background_viral_particles: Vec<f32>,
 
     // Vec of (x, y) offsets that are in the exhale radius
     cells_in_exhale_radius: Vec<(i32, i32)>,
+    // Share of the exhaled particles that lands in each of cells_in_exhale_radius, for each
+    // direction that people can face in.
+    // Kernels that do not depend on the direction only have a single set of weights.
+    exhale_weights: Vec<Vec<f32>>,
+
+    // Where each person was and which direction they were facing on the previous tick.
+    // Only tracked for kernels that depend on the direction.
+    last_positions: Vec<Position>,
+    direction_idxs: Vec<usize>,
+
+    // Dose that each person has accumulated, if using the dose infection model.
+    doses: Vec<f32>,
 }
 
-impl BackgroundViralParticleDiseaseSpreader {
-    pub(crate) fn new(world_bb: BoundingBox, params: BackgroundViralParticleParams) -> Self {
-        let background_viral_particles = vec![0.0; world_bb.size()];
+// Barrier flags for each cell.
+// The edge between the cell and the next cell over in the x direction blocks particles.
+const BLOCKED_X: u8 = 1;
+// The edge between the cell and the next cell over in the y direction blocks particles.
+const BLOCKED_Y: u8 = 2;
+// The cell is a wall, which never holds particles.
+const WALL: u8 = 4;
 
-        let mut cells_in_exhale_radius = vec![];
-        for x in -params.exhale_radius as i32..params.exhale_radius as i32 {
-            for y in -params.exhale_radius as i32..params.exhale_radius as i32 {
-                if x * x + y * y <= (params.exhale_radius * params.exhale_radius) as i32 {
-                    cells_in_exhale_radius.push((x, y));
-                }
+fn is_open(barriers: &[u8], idx: usize, flag: u8) -> bool {
+    barriers.is_empty() || barriers[idx] & flag == 0
+}
+
+// Whether particles can travel in a straight line from the cell at (x, y) to the cell at
+// (x + dx, y + dy) without crossing a barrier.
+// The line is followed one neighboring cell at a time, so it cannot slip through corners.
+fn is_visible(barriers: &[u8], width: usize, x: usize, y: usize, dx: i32, dy: i32) -> bool {
+    if barriers.is_empty() {
+        return true;
+    }
+
+    let (num_x, num_y) = (dx.unsigned_abs() as usize, dy.unsigned_abs() as usize);
+    let (mut x, mut y) = (x, y);
+    let (mut steps_x, mut steps_y) = (0, 0);
+    while steps_x < num_x || steps_y < num_y {
+        // Step along whichever axis the line crosses into the next cell first.
+        let step_x = steps_y == num_y
+            || (steps_x < num_x && (2 * steps_x + 1) * num_y <= (2 * steps_y + 1) * num_x);
+        if step_x {
+            let edge_x = if dx > 0 { x } else { x - 1 };
+            if !is_open(barriers, width * y + edge_x, BLOCKED_X) {
+                return false;
+            }
+            x = if dx > 0 { x + 1 } else { x - 1 };
+            steps_x += 1;
+        } else {
+            let edge_y = if dy > 0 { y } else { y - 1 };
+            if !is_open(barriers, width * edge_y + x, BLOCKED_Y) {
+                return false;
             }
+            y = if dy > 0 { y + 1 } else { y - 1 };
+            steps_y += 1;
         }
+    }
+
+    true
+}
+
+// Number of directions that directional exhale kernels are precomputed for.
+const NUM_EXHALE_DIRECTIONS: usize = 16;
+
+// Angle of the offset, using the same convention as Position::advance.
+fn get_angle(dx: f32, dy: f32) -> f32 {
+    normalize_angle((-dy).atan2(dx))
+}
+
+impl BackgroundViralParticleDiseaseSpreader {
+    pub(crate) fn new(
+        world_bb: BoundingBox,
+        params: BackgroundViralParticleParams,
+        map: Option<&Map>,
+    ) -> Self {
+        let background_viral_particles = vec![0.0; world_bb.size()];
+        let survival_rates = Self::get_survival_rates(&world_bb, &params, map);
+        let barriers = Self::get_barriers(&world_bb, &params, map);
+        let cells_in_exhale_radius = Self::get_cells_in_exhale_radius(params.exhale_radius);
+        let exhale_weights =
+            Self::get_exhale_weights(&params.exhale_kernel, &cells_in_exhale_radius);
 
         BackgroundViralParticleDiseaseSpreader {
             world_bounding_box: world_bb,
             params,
             background_viral_particles,
+            survival_rates,
+            barriers,
+            next_background_viral_particles: vec![],
             cells_in_exhale_radius,
+            exhale_weights,
+            last_positions: vec![],
+            direction_idxs: vec![],
+            doses: vec![],
+        }
+    }
+
+    fn get_survival_rates(
+        world_bb: &BoundingBox,
+        params: &BackgroundViralParticleParams,
+        map: Option<&Map>,
+    ) -> Vec<f32> {
+        if params.ventilation.is_empty() {
+            return vec![];
+        }
+
+        let width = world_bb.right;
+        let mut survival_rates = vec![1.0 - params.decay_rate; world_bb.size()];
+        for ventilation in params.ventilation.iter() {
+            let survival_rate =
+                (1.0 - ventilation.decay_rate) * (1.0 - ventilation.air_exchange_rate);
+
+            let bounds = match ventilation.target {
+                VentilationTarget::MapElement(element) => {
+                    for y in world_bb.rows() {
+                        for x in world_bb.cols() {
+                            // Without a map, the whole world is background.
+                            let cell_element =
+                                map.map_or(MapElement::Background, |m| m.get_element(y, x));
+                            if cell_element == element {
+                                survival_rates[width * y + x] = survival_rate;
+                            }
+                        }
+                    }
+                    continue;
+                }
+                VentilationTarget::Household(idx) => {
+                    map.expect("household ventilation requires a map")
+                        .households[idx]
+                        .bounds
+                }
+                VentilationTarget::Store(idx) => {
+                    map.expect("store ventilation requires a map").stores[idx].bounds
+                }
+            };
+            for y in bounds.rows() {
+                for x in bounds.cols() {
+                    survival_rates[width * y + x] = survival_rate;
+                }
+            }
+        }
+
+        survival_rates
+    }
+
+    fn get_barriers(
+        world_bb: &BoundingBox,
+        params: &BackgroundViralParticleParams,
+        map: Option<&Map>,
+    ) -> Vec<u8> {
+        // Without a map, there are no boundaries.
+        let map = match map {
+            Some(map) if params.walls => map,
+            _ => return vec![],
+        };
+
+        // Each household and store is its own region, while everything else is split up by the
+        // kind of map element.
+        let width = world_bb.right;
+        let mut regions = vec![0; world_bb.size()];
+        for y in world_bb.rows() {
+            for x in world_bb.cols() {
+                regions[width * y + x] = match map.get_element(y, x) {
+                    MapElement::Background => 0,
+                    MapElement::Road | MapElement::StoreQueue => 1,
+                    MapElement::Wall => 2,
+                    // Overwritten below.
+                    MapElement::Household | MapElement::Store => 3,
+                };
+            }
+        }
+        let buildings = map
+            .households
+            .iter()
+            .map(|h| h.bounds)
+            .chain(map.stores.iter().map(|s| s.bounds))
+            .collect::<Vec<_>>();
+        for (building_idx, bounds) in buildings.iter().enumerate() {
+            for y in bounds.rows() {
+                for x in bounds.cols() {
+                    regions[width * y + x] = 3 + building_idx;
+                }
+            }
+        }
+
+        let mut barriers = vec![0; world_bb.size()];
+        for y in world_bb.rows() {
+            for x in world_bb.cols() {
+                let idx = width * y + x;
+                if regions[idx] == 2 {
+                    barriers[idx] |= WALL;
+                }
+                if x + 1 < width && regions[idx] != regions[idx + 1] {
+                    barriers[idx] |= BLOCKED_X;
+                }
+                if y + 1 < world_bb.top && regions[idx] != regions[idx + width] {
+                    barriers[idx] |= BLOCKED_Y;
+                }
+            }
+        }
+
+        // Open up the doorways, which are given as (row, col) pairs of neighboring cells.
+        for bounds in buildings.iter() {
+            for (inside, road) in map.find_bb_road_intersection(bounds) {
+                let (y1, x1) = (inside.0.min(road.0) as usize, inside.1.min(road.1) as usize);
+                let flag = if inside.0 == road.0 {
+                    BLOCKED_X
+                } else {
+                    BLOCKED_Y
+                };
+                barriers[width * y1 + x1] &= !flag;
+            }
+        }
+
+        barriers
+    }
+
+    fn get_exhale_weights(kernel: &ExhaleKernel, cells: &[(i32, i32)]) -> Vec<Vec<f32>> {
+        let num_directions = match kernel {
+            ExhaleKernel::Cone { .. } => NUM_EXHALE_DIRECTIONS,
+            _ => 1,
+        };
+
+        (0..num_directions)
+            .map(|direction_idx| {
+                let direction_rad = direction_idx as f32 * 2.0 * PI / NUM_EXHALE_DIRECTIONS as f32;
+
+                let mut weights = cells
+                    .iter()
+                    .map(|&(dx, dy)| {
+                        let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                        match kernel {
+                            ExhaleKernel::Uniform => 1.0,
+                            ExhaleKernel::Gaussian { stddev } => {
+                                (-distance * distance / (2.0 * stddev * stddev)).exp()
+                            }
+                            ExhaleKernel::InverseSquare { min_distance } => {
+                                1.0 / distance.max(*min_distance).powi(2)
+                            }
+                            ExhaleKernel::Cone { half_angle_rad } => {
+                                let angle_diff = normalize_angle(
+                                    get_angle(dx as f32, dy as f32) - direction_rad,
+                                );
+                                // The person's own cell is always in the cone.
+                                if distance == 0.0
+                                    || angle_diff.min(2.0 * PI - angle_diff) <= *half_angle_rad
+                                {
+                                    1.0
+                                } else {
+                                    0.0
+                                }
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                // Scale the weights so that the same amount of particles is exhaled as with the
+                // uniform kernel, which puts the full amount into every cell.
+                let total_weight = weights.iter().sum::<f32>();
+                if total_weight > 0.0 {
+                    let scale = cells.len() as f32 / total_weight;
+                    for weight in weights.iter_mut() {
+                        *weight *= scale;
+                    }
+                }
+
+                weights
+            })
+            .collect()
+    }
+
+    // Updates the direction that each person is facing in from how they moved since the last
+    // tick. People who did not move keep facing the same way.
+    fn update_directions(&mut self, people: &[Person]) {
+        if self.exhale_weights.len() == 1 {
+            return;
+        }
+
+        if self.last_positions.len() != people.len() {
+            self.last_positions = people.iter().map(|p| p.position.clone()).collect();
+            self.direction_idxs = vec![0; people.len()];
+        }
+
+        for (idx, p) in people.iter().enumerate() {
+            let last_position = &mut self.last_positions[idx];
+            let dx = p.position.x - last_position.x;
+            let dy = p.position.y - last_position.y;
+            if dx != 0.0 || dy != 0.0 {
+                let direction_idx = (get_angle(dx, dy) * NUM_EXHALE_DIRECTIONS as f32 / (2.0 * PI))
+                    .round() as usize;
+                self.direction_idxs[idx] = direction_idx % NUM_EXHALE_DIRECTIONS;
+            }
+            *last_position = p.position.clone();
+        }
+    }
+
+    fn get_cells_in_exhale_radius(exhale_radius: f32) -> Vec<(i32, i32)> {
+        let mut cells_in_exhale_radius = vec![];
+        for x in -exhale_radius as i32..exhale_radius as i32 {
+            for y in -exhale_radius as i32..exhale_radius as i32 {
+                if x * x + y * y <= (exhale_radius * exhale_radius) as i32 {
+                    cells_in_exhale_radius.push((x, y));
+                }
+            }
         }
+        cells_in_exhale_radius
     }
 }
 
@@ -110,14 +906,16 @@ impl BackgroundViralParticleDiseaseSpreader {
         &mut particles[idx]
     }
 
-    fn update_background(&mut self, pos: &Position, particles_exhaled: f32) {
+    fn update_background(&mut self, pos: &Position, direction_idx: usize, particles_exhaled: f32) {
         let left = self.world_bounding_box.left as f32;
         let right = self.world_bounding_box.right as f32;
 
         let top = self.world_bounding_box.top as f32;
         let bottom = self.world_bounding_box.bottom as f32;
 
-        for (dx, dy) in self.cells_in_exhale_radius.iter() {
+        let width = self.world_bounding_box.right;
+        let weights = &self.exhale_weights[direction_idx];
+        for ((dx, dy), weight) in self.cells_in_exhale_radius.iter().zip(weights.iter()) {
             let x = pos.x + *dx as f32;
             if x < left || x >= right {
                 continue;
@@ -126,17 +924,42 @@ impl BackgroundViralParticleDiseaseSpreader {
             if y < bottom || y >= top {
                 continue;
             }
+            if !is_open(&self.barriers, width * y as usize + x as usize, WALL)
+                || !is_visible(
+                    &self.barriers,
+                    width,
+                    pos.x as usize,
+                    pos.y as usize,
+                    *dx,
+                    *dy,
+                )
+            {
+                continue;
+            }
 
             *Self::mut_particles_at(
                 &mut self.background_viral_particles,
                 self.world_bounding_box.right,
                 x as usize,
                 y as usize,
-            ) += particles_exhaled;
+            ) += particles_exhaled * weight;
         }
     }
 
     fn decay_existing_particles(&mut self) {
+        if !self.survival_rates.is_empty() {
+            for (val, survival_rate) in self
+                .background_viral_particles
+                .iter_mut()
+                .zip(self.survival_rates.iter())
+            {
+                if *val >= f32::MIN_POSITIVE {
+                    *val *= survival_rate;
+                }
+            }
+            return;
+        }
+
         let viral_particle_survival_rate = 1.0 - self.params.decay_rate;
         for val in self.background_viral_particles.iter_mut() {
             // This branch makes the loop function run significantly faster under WASM, but slower
@@ -147,63 +970,1526 @@ impl BackgroundViralParticleDiseaseSpreader {
         }
     }
 
-    fn expose_susceptible_people(&self, tick: usize, rng: &mut dyn RngCore, people: &mut [Person]) {
-        for p in people.iter_mut() {
-            if let DiseaseState::Susceptible = p.disease_state {
-            } else {
-                continue;
-            }
+    // Moves particles between neighboring cells. Particles that would leave the world or cross a
+    // barrier stay in their cell, so the total amount of particles does not change.
+    fn move_existing_particles(&mut self) {
+        let params = if let Some(params) = self.params.air_movement {
+            params
+        } else {
+            return;
+        };
 
-            let particles = self.get_particles_at(p.position.x as usize, p.position.y as usize);
-            let particles_inhaled = match p.mask {
-                Mask::N95 => particles / 5.0,
-                _ => particles,
-            };
-            if particles_inhaled <= f32::MIN_POSITIVE {
-                continue;
-            }
+        let width = self.world_bounding_box.right;
+        let height = self.world_bounding_box.top;
+        let barriers = &self.barriers;
 
-            let infection_risk = particles_inhaled * self.params.infection_risk_per_particle;
+        // Diffusion: every pair of neighboring cells exchanges particles in proportion to the
+        // difference between them.
+        if params.diffusion_coefficient > 0.0 {
+            let particles = &self.background_viral_particles;
+            let next = &mut self.next_background_viral_particles;
+            next.clear();
+            next.extend_from_slice(particles);
 
-            if rng.gen::<f32>() > infection_risk {
-                continue;
+            for y in 0..height {
+                for x in 0..width {
+                    let idx = width * y + x;
+                    if x + 1 < width && is_open(barriers, idx, BLOCKED_X) {
+                        let flow =
+                            params.diffusion_coefficient * (particles[idx] - particles[idx + 1]);
+                        next[idx] -= flow;
+                        next[idx + 1] += flow;
+                    }
+                    if y + 1 < height && is_open(barriers, idx, BLOCKED_Y) {
+                        let flow = params.diffusion_coefficient
+                            * (particles[idx] - particles[idx + width]);
+                        next[idx] -= flow;
+                        next[idx + width] += flow;
+                    }
+                }
             }
-            p.disease_state = DiseaseState::Exposed(tick);
+            std::mem::swap(
+                &mut self.background_viral_particles,
+                &mut self.next_background_viral_particles,
+            );
         }
-    }
 
-    fn infectious_people_exhale(&mut self, people: &mut [Person]) {
-        for p in people.iter_mut() {
-            if let DiseaseState::Infectious(_) = p.disease_state {
-            } else {
-                continue;
-            }
+        // Advection: the wind carries a fixed fraction of each cell's particles downwind.
+        if params.wind_x != 0.0 || params.wind_y != 0.0 {
+            let particles = &self.background_viral_particles;
+            let next = &mut self.next_background_viral_particles;
+            next.clear();
+            next.extend_from_slice(particles);
 
-            let particles_exhaled = match p.mask {
-                Mask::N95 => 0.2,
-                Mask::Regular => 0.2,
-                Mask::None => 1.0,
-            };
+            for y in 0..height {
+                for x in 0..width {
+                    let idx = width * y + x;
+                    let val = particles[idx];
+                    if val < f32::MIN_POSITIVE {
+                        continue;
+                    }
+
+                    if params.wind_x > 0.0 && x + 1 < width && is_open(barriers, idx, BLOCKED_X) {
+                        next[idx] -= val * params.wind_x;
+                        next[idx + 1] += val * params.wind_x;
+                    } else if params.wind_x < 0.0 && x > 0 && is_open(barriers, idx - 1, BLOCKED_X)
+                    {
+                        next[idx] += val * params.wind_x;
+                        next[idx - 1] -= val * params.wind_x;
+                    }
+                    if params.wind_y > 0.0 && y + 1 < height && is_open(barriers, idx, BLOCKED_Y) {
+                        next[idx] -= val * params.wind_y;
+                        next[idx + width] += val * params.wind_y;
+                    } else if params.wind_y < 0.0
+                        && y > 0
+                        && is_open(barriers, idx - width, BLOCKED_Y)
+                    {
+                        next[idx] += val * params.wind_y;
+                        next[idx - width] -= val * params.wind_y;
+                    }
+                }
+            }
+            std::mem::swap(
+                &mut self.background_viral_particles,
+                &mut self.next_background_viral_particles,
+            );
+        }
+    }
+
+    fn expose_susceptible_people(
+        &mut self,
+        tick: usize,
+        rng: &mut dyn RngCore,
+        people: &mut [Person],
+    ) -> Vec<Exposure> {
+        let dose_params = match self.params.infection_model {
+            InfectionModel::PerTick => return self.expose_per_tick(tick, rng, people),
+            InfectionModel::Dose(dose_params) => dose_params,
+        };
+        if self.doses.len() != people.len() {
+            self.doses = vec![0.0; people.len()];
+        }
+
+        let mut exposures = vec![];
+        let dose_survival_rate = 1.0 - dose_params.dose_decay_rate;
+        for (p, dose) in people.iter_mut().zip(self.doses.iter_mut()) {
+            if let DiseaseState::Susceptible = p.disease_state {
+            } else {
+                // The dose only matters for people who can still be infected.
+                *dose = 0.0;
+                continue;
+            }
+
+            // The baseline for this tick's risk is what is left of the dose after decay, so that
+            // a dose that has levelled off under constant exposure still carries a risk.
+            *dose *= dose_survival_rate;
+            let prev_dose = *dose;
+
+            let cell = (p.position.x as usize, p.position.y as usize);
+            let particles =
+                self.background_viral_particles[self.world_bounding_box.right * cell.1 + cell.0];
+            let particles_inhaled =
+                particles * p.mask_filtration.inhale * dose_params.breathing_rate;
+            if particles_inhaled <= f32::MIN_POSITIVE {
+                continue;
+            }
+            *dose += particles_inhaled;
+
+            let infection_risk =
+                dose_params.infection_probability(prev_dose, *dose) * p.get_susceptibility();
+            if rng.gen::<f32>() > infection_risk {
+                continue;
+            }
+            p.disease_state = DiseaseState::Exposed(tick);
+            exposures.push(Exposure {
+                infectee: p.id,
+                source: TransmissionSource::ViralParticles { dose: *dose, cell },
+                route: None,
+            });
+            *dose = 0.0;
+        }
+
+        exposures
+    }
+
+    fn expose_per_tick(
+        &self,
+        tick: usize,
+        rng: &mut dyn RngCore,
+        people: &mut [Person],
+    ) -> Vec<Exposure> {
+        let mut exposures = vec![];
+
+        for p in people.iter_mut() {
+            if let DiseaseState::Susceptible = p.disease_state {
+            } else {
+                continue;
+            }
+
+            let cell = (p.position.x as usize, p.position.y as usize);
+            let particles = self.get_particles_at(cell.0, cell.1);
+            let particles_inhaled = particles * p.mask_filtration.inhale;
+            if particles_inhaled <= f32::MIN_POSITIVE {
+                continue;
+            }
 
-            self.update_background(&p.position, particles_exhaled);
+            let infection_risk = particles_inhaled
+                * self.params.infection_risk_per_particle
+                * p.get_susceptibility();
+
+            if rng.gen::<f32>() > infection_risk {
+                continue;
+            }
+            p.disease_state = DiseaseState::Exposed(tick);
+            exposures.push(Exposure {
+                infectee: p.id,
+                source: TransmissionSource::ViralParticles {
+                    dose: particles_inhaled,
+                    cell,
+                },
+                route: None,
+            });
+        }
+
+        exposures
+    }
+
+    fn infectious_people_exhale(&mut self, people: &mut [Person]) {
+        self.update_directions(people);
+
+        for (idx, p) in people.iter_mut().enumerate() {
+            let exhale_multiplier =
+                if let Some(multiplier) = self.params.exhale_multipliers.get(&p.disease_state) {
+                    multiplier
+                } else {
+                    continue;
+                };
+
+            let particles_exhaled =
+                exhale_multiplier * p.vaccine_infectiousness * p.mask_filtration.exhale;
+
+            let direction_idx = if self.exhale_weights.len() == 1 {
+                0
+            } else {
+                self.direction_idxs[idx]
+            };
+            self.update_background(&p.position, direction_idx, particles_exhaled);
         }
     }
 }
 
 impl DiseaseSpreader for BackgroundViralParticleDiseaseSpreader {
-    fn spread(&mut self, tick: usize, rng: &mut dyn RngCore, people: &mut [Person]) {
-        // Step 1: Decay the existing particles
+    fn spread(
+        &mut self,
+        tick: usize,
+        rng: &mut dyn RngCore,
+        people: &mut [Person],
+    ) -> Vec<Exposure> {
+        // Step 1: Decay the existing particles, and let the air move the rest around.
         self.decay_existing_particles();
+        self.move_existing_particles();
 
         // Step 2: All people inhale, and may become exposed according to how much they have
         // inhaled.
-        self.expose_susceptible_people(tick, rng, people);
+        let exposures = self.expose_susceptible_people(tick, rng, people);
 
         // Step 3: All people exhale, and infected people spread viral particles.
         self.infectious_people_exhale(people);
+
+        exposures
+    }
+
+    fn checkpoint(&self) -> DiseaseSpreaderCheckpoint {
+        DiseaseSpreaderCheckpoint::BackgroundViralParticle(Box::new(self.clone()))
+    }
+
+    fn set_parameters(&mut self, params: &DiseaseSpreadParameters, map: Option<&Map>) -> bool {
+        if let DiseaseSpreadParameters::BackgroundViralParticle(params) = params {
+            // The particles already in the world are kept as is.
+            self.params = params.clone();
+            self.survival_rates = Self::get_survival_rates(&self.world_bounding_box, params, map);
+            self.barriers = Self::get_barriers(&self.world_bounding_box, params, map);
+            self.cells_in_exhale_radius = Self::get_cells_in_exhale_radius(params.exhale_radius);
+            self.exhale_weights =
+                Self::get_exhale_weights(&params.exhale_kernel, &self.cells_in_exhale_radius);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn get_background_viral_levels(&self) -> Option<&Vec<f32>> {
+        Some(&self.background_viral_particles)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Household Spread
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Deserialize, Serialize)]
+pub(crate) struct HouseholdDiseaseSpreader {
+    params: HouseholdTransmissionParams,
+    household_bounds: Vec<BoundingBox>,
+}
+
+impl HouseholdDiseaseSpreader {
+    pub(crate) fn new(params: HouseholdTransmissionParams, map: Option<&Map>) -> Self {
+        HouseholdDiseaseSpreader {
+            params,
+            household_bounds: map
+                .map_or(vec![], |m| m.households.iter().map(|h| h.bounds).collect()),
+        }
+    }
+
+    fn is_at_home(&self, p: &Person) -> bool {
+        let bounds = &self.household_bounds[p.household_idx];
+        bounds.cols().contains(&(p.position.x as usize))
+            && bounds.rows().contains(&(p.position.y as usize))
+    }
+
+    // Probability that the infector exposes the susceptible person during this tick.
+    fn get_probability(&self, susceptible: &Person, infector: &Person) -> f32 {
+        let multiplier = match self
+            .params
+            .infectiousness_multipliers
+            .get(&infector.disease_state)
+        {
+            Some(multiplier) => multiplier,
+            None => return 0.0,
+        };
+        if self.params.only_at_home && !(self.is_at_home(susceptible) && self.is_at_home(infector))
+        {
+            return 0.0;
+        }
+
+        self.params.hazard_per_tick
+            * multiplier
+            * susceptible.get_susceptibility()
+            * infector.vaccine_infectiousness
+    }
+}
+
+impl DiseaseSpreader for HouseholdDiseaseSpreader {
+    fn spread(
+        &mut self,
+        tick: usize,
+        rng: &mut dyn RngCore,
+        people: &mut [Person],
+    ) -> Vec<Exposure> {
+        let mut infectious_people_by_household = vec![vec![]; self.household_bounds.len()];
+        for (idx, p) in people.iter().enumerate() {
+            if p.disease_state.is_infectious() {
+                infectious_people_by_household[p.household_idx].push(idx);
+            }
+        }
+
+        // Each infectious member of the household gets an independent chance to expose a
+        // susceptible member, in index order, until one of them succeeds.
+        let mut exposures = vec![];
+        for idx in 0..people.len() {
+            if people[idx].disease_state != DiseaseState::Susceptible {
+                continue;
+            }
+
+            let infector_idx = infectious_people_by_household[people[idx].household_idx]
+                .iter()
+                .find(|infector_idx| {
+                    let p_infect = self.get_probability(&people[idx], &people[**infector_idx]);
+                    p_infect > 0.0 && rng.gen_bool(p_infect.min(1.0) as f64)
+                });
+            if let Some(infector_idx) = infector_idx {
+                people[idx].disease_state = DiseaseState::Exposed(tick);
+                exposures.push(Exposure {
+                    infectee: people[idx].id,
+                    source: TransmissionSource::Household {
+                        infector: people[*infector_idx].id,
+                    },
+                    route: None,
+                });
+            }
+        }
+
+        exposures
+    }
+
+    fn checkpoint(&self) -> DiseaseSpreaderCheckpoint {
+        DiseaseSpreaderCheckpoint::Household(self.clone())
+    }
+
+    fn set_parameters(&mut self, params: &DiseaseSpreadParameters, _map: Option<&Map>) -> bool {
+        if let DiseaseSpreadParameters::Household(params) = params {
+            self.params = *params;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Composite Spread
+///////////////////////////////////////////////////////////////////////////////
+
+pub(crate) struct CompositeDiseaseSpreader {
+    spreaders: Vec<Box<dyn DiseaseSpreader>>,
+}
+
+impl DiseaseSpreader for CompositeDiseaseSpreader {
+    fn spread(
+        &mut self,
+        tick: usize,
+        rng: &mut dyn RngCore,
+        people: &mut [Person],
+    ) -> Vec<Exposure> {
+        let mut exposures = vec![];
+        for (route, spreader) in self.spreaders.iter_mut().enumerate() {
+            exposures.extend(
+                spreader
+                    .spread(tick, rng, people)
+                    .into_iter()
+                    .map(|exposure| Exposure {
+                        route: Some(route),
+                        ..exposure
+                    }),
+            );
+        }
+        exposures
+    }
+
+    fn checkpoint(&self) -> DiseaseSpreaderCheckpoint {
+        DiseaseSpreaderCheckpoint::Composite(
+            self.spreaders.iter().map(|s| s.checkpoint()).collect(),
+        )
+    }
+
+    fn set_parameters(&mut self, params: &DiseaseSpreadParameters, map: Option<&Map>) -> bool {
+        match params {
+            DiseaseSpreadParameters::Composite(routes) if routes.len() == self.spreaders.len() => {
+                // Each route must keep the same spread model. Otherwise, the whole composite is
+                // rebuilt.
+                self.spreaders
+                    .iter_mut()
+                    .zip(routes.iter())
+                    .all(|(spreader, params)| spreader.set_parameters(params, map))
+            }
+            _ => false,
+        }
+    }
+
+    // Uses the first route with background viral particles.
+    fn get_background_viral_levels(&self) -> Option<&Vec<f32>> {
+        self.spreaders
+            .iter()
+            .find_map(|spreader| spreader.get_background_viral_levels())
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Fomite Spread
+///////////////////////////////////////////////////////////////////////////////
+
+// Contamination of store surfaces.
+#[derive(Clone, Deserialize, Serialize)]
+pub(crate) struct FomiteDiseaseSpreader {
+    world_bounding_box: BoundingBox,
+    params: FomiteParams,
+    // Only store cells get contaminated.
+    store_bounds: Vec<BoundingBox>,
+    contamination: Vec<f32>,
+    next_cleaning_tick: usize,
+}
+
+impl FomiteDiseaseSpreader {
+    pub(crate) fn new(world_bb: BoundingBox, params: FomiteParams, map: Option<&Map>) -> Self {
+        let store_bounds = map.map_or(vec![], |m| m.stores.iter().map(|s| s.bounds).collect());
+        FomiteDiseaseSpreader {
+            world_bounding_box: world_bb,
+            params,
+            store_bounds,
+            contamination: vec![0.0; world_bb.size()],
+            next_cleaning_tick: params.cleaning.map_or(0, |cleaning| cleaning.period_ticks),
+        }
+    }
+
+    // Returns the store cell that the person is on, if any.
+    fn get_store_cell(&self, p: &Person) -> Option<(usize, usize)> {
+        let cell = (p.position.x as usize, p.position.y as usize);
+        let in_store = self
+            .store_bounds
+            .iter()
+            .any(|bb| bb.cols().contains(&cell.0) && bb.rows().contains(&cell.1));
+        if in_store {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    fn decay_and_clean(&mut self, tick: usize) {
+        let mut survival_rate = 1.0 - self.params.decay_rate;
+        if let Some(cleaning) = self.params.cleaning {
+            if tick >= self.next_cleaning_tick {
+                survival_rate *= 1.0 - cleaning.efficacy;
+                self.next_cleaning_tick += cleaning.period_ticks;
+            }
+        }
+
+        for val in self.contamination.iter_mut() {
+            if *val >= f32::MIN_POSITIVE {
+                *val *= survival_rate;
+            }
+        }
+    }
+}
+
+impl DiseaseSpreader for FomiteDiseaseSpreader {
+    fn spread(
+        &mut self,
+        tick: usize,
+        rng: &mut dyn RngCore,
+        people: &mut [Person],
+    ) -> Vec<Exposure> {
+        let width = self.world_bounding_box.right;
+
+        // Step 1: Decay the existing contamination, and clean the stores if it is time to.
+        self.decay_and_clean(tick);
+
+        // Step 2: Susceptible people pick up contamination from where they are standing, and may
+        // become exposed.
+        let mut exposures = vec![];
+        for p in people.iter_mut() {
+            if p.disease_state != DiseaseState::Susceptible {
+                continue;
+            }
+            let cell = match self.get_store_cell(p) {
+                Some(cell) => cell,
+                None => continue,
+            };
+
+            let contamination = &mut self.contamination[width * cell.1 + cell.0];
+            let picked_up = *contamination * self.params.pickup_fraction;
+            if picked_up <= f32::MIN_POSITIVE {
+                continue;
+            }
+            *contamination -= picked_up;
+
+            let infection_risk =
+                picked_up * self.params.infection_risk_per_unit * p.get_susceptibility();
+            if rng.gen::<f32>() > infection_risk {
+                continue;
+            }
+            p.disease_state = DiseaseState::Exposed(tick);
+            exposures.push(Exposure {
+                infectee: p.id,
+                source: TransmissionSource::Fomite {
+                    dose: picked_up,
+                    cell,
+                },
+                route: None,
+            });
+        }
+
+        // Step 3: Infectious people contaminate where they are standing.
+        for p in people.iter() {
+            let multiplier = match self.params.deposit_multipliers.get(&p.disease_state) {
+                Some(multiplier) => multiplier,
+                None => continue,
+            };
+            if let Some(cell) = self.get_store_cell(p) {
+                self.contamination[width * cell.1 + cell.0] +=
+                    self.params.deposit_rate * multiplier * p.vaccine_infectiousness;
+            }
+        }
+
+        exposures
+    }
+
+    fn checkpoint(&self) -> DiseaseSpreaderCheckpoint {
+        DiseaseSpreaderCheckpoint::Fomite(self.clone())
+    }
+
+    // The contamination already on surfaces is kept.
+    fn set_parameters(&mut self, params: &DiseaseSpreadParameters, _map: Option<&Map>) -> bool {
+        if let DiseaseSpreadParameters::Fomite(params) = params {
+            self.params = *params;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v0::config::{CleaningParams, MapParams};
+    use crate::v0::types::Mask;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // Reference implementation of the infection radius spread that compares every pair of people.
+    fn spread_pairwise(
+        tick: usize,
+        params: &InfectionRadiusParams,
+        people: &mut [Person],
+    ) -> Vec<(usize, usize)> {
+        let mut exposures = vec![];
+        let get_radius = |p: &Person| {
+            params
+                .radius_multipliers
+                .get(&p.disease_state)
+                .map_or(0.0, |multiplier| params.radius * multiplier)
+        };
+
+        for i in 0..(people.len() - 1) {
+            let (left, right) = people.split_at_mut(i + 1);
+            let p0 = left.last_mut().unwrap();
+
+            for p1 in right.iter_mut() {
+                let distance = p0.closest_distance(p1);
+
+                if p0.disease_state == DiseaseState::Susceptible && distance < get_radius(p1) {
+                    p0.disease_state = DiseaseState::Exposed(tick);
+                    exposures.push((p0.id, p1.id));
+                } else if p1.disease_state == DiseaseState::Susceptible && distance < get_radius(p0)
+                {
+                    p1.disease_state = DiseaseState::Exposed(tick);
+                    exposures.push((p1.id, p0.id));
+                }
+            }
+        }
+
+        exposures
+    }
+
+    fn random_people(
+        world_bb: &BoundingBox,
+        num_people: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Person> {
+        (0..num_people)
+            .map(|id| {
+                let disease_state = match rng.gen_range(0, 6) {
+                    0 => DiseaseState::Presymptomatic(0),
+                    1 => DiseaseState::Asymptomatic(0),
+                    2 => DiseaseState::Symptomatic(0),
+                    3 => DiseaseState::Exposed(0),
+                    4 => DiseaseState::Recovered(0),
+                    _ => DiseaseState::Susceptible,
+                };
+
+                let position = world_bb.random_point(rng);
+                Person {
+                    id,
+                    disease_state,
+                    last_position: position.clone(),
+                    position,
+                    household_idx: 0,
+                    head_of_household: false,
+                    mask: Mask::None,
+                    mask_fit: 1.0,
+                    mask_filtration: MaskFiltrationTable::default().get(Mask::None, 1.0),
+                    susceptibility: 1.0,
+                    disease_period_ticks: None,
+                    vaccinated_tick: None,
+                    vaccine_susceptibility: 1.0,
+                    vaccine_infectiousness: 1.0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_infection_radius_matches_pairwise_spread() {
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 40,
+            right: 60,
+        };
+
+        let uniform_radius = InfectionRadiusParams {
+            radius: 3.2,
+            radius_multipliers: InfectiousStateValues::default(),
+        };
+        let radius_by_state = InfectionRadiusParams {
+            radius: 3.2,
+            radius_multipliers: InfectiousStateValues {
+                presymptomatic: 0.5,
+                asymptomatic: 0.0,
+                symptomatic: 1.5,
+            },
+        };
+
+        for params in &[uniform_radius, radius_by_state] {
+            for num_people in &[1, 2, 10, 200, 1000] {
+                // Everyone walked up to 3 units in each direction during the tick.
+                let mut people = random_people(&world_bb, *num_people, &mut rng);
+                for p in people.iter_mut() {
+                    p.last_position.x += rng.gen_range(-3.0, 3.0);
+                    p.last_position.y += rng.gen_range(-3.0, 3.0);
+                }
+                let mut expected_people = people.clone();
+
+                let mut spreader = InfectionRadiusDiseaseSpreader::new(world_bb, *params);
+                let exposures = spreader
+                    .spread(1, &mut rng, &mut people)
+                    .into_iter()
+                    .map(|e| (e.infectee, e.source.infector().unwrap()))
+                    .collect::<Vec<_>>();
+
+                let expected_exposures = if *num_people > 1 {
+                    spread_pairwise(1, params, &mut expected_people)
+                } else {
+                    vec![]
+                };
+
+                assert_eq!(exposures, expected_exposures);
+                for (p, expected_p) in people.iter().zip(expected_people.iter()) {
+                    assert_eq!(p.disease_state, expected_p.disease_state);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_infection_radius_catches_people_walking_past_each_other() {
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 20,
+            right: 20,
+        };
+        let mut spreader = InfectionRadiusDiseaseSpreader::new(
+            world_bb,
+            InfectionRadiusParams {
+                radius: 1.0,
+                radius_multipliers: InfectiousStateValues::default(),
+            },
+        );
+
+        // Two people walk 3 units along a road in opposite directions, and end the tick 2 units
+        // apart on the other side of each other.
+        let mut people = random_people(&world_bb, 2, &mut rng);
+        people[0].disease_state = DiseaseState::Symptomatic(0);
+        people[0].last_position = Position { x: 8.5, y: 10.5 };
+        people[0].position = Position { x: 11.5, y: 10.5 };
+        people[1].disease_state = DiseaseState::Susceptible;
+        people[1].last_position = Position { x: 12.5, y: 10.5 };
+        people[1].position = Position { x: 9.5, y: 10.5 };
+
+        let exposures = spreader.spread(1, &mut rng, &mut people);
+        assert_eq!(exposures.len(), 1);
+        assert_eq!(exposures[0].infectee, 1);
+
+        // Walking the same way, they stay apart.
+        people[1].disease_state = DiseaseState::Susceptible;
+        people[1].last_position = Position { x: 11.5, y: 10.5 };
+        people[1].position = Position { x: 14.5, y: 10.5 };
+        assert!(spreader.spread(2, &mut rng, &mut people).is_empty());
+    }
+
+    #[test]
+    fn test_mask_filtration_fit() {
+        let table = MaskFiltrationTable::default();
+
+        assert_eq!(table.get(Mask::N95, 1.0), table.n95);
+        assert_eq!(table.get(Mask::None, 0.3), table.none);
+        assert_eq!(
+            table.get(Mask::Respirator, 0.0),
+            MaskFiltration {
+                inhale: 1.0,
+                exhale: 1.0,
+            }
+        );
+
+        let loose_surgical = table.get(Mask::Surgical, 0.5);
+        approx::assert_ulps_eq!(loose_surgical.inhale, 0.8);
+        approx::assert_ulps_eq!(loose_surgical.exhale, 0.65);
+
+        let mut invalid_table = table;
+        invalid_table.cloth.inhale = 1.5;
+        assert!(table.is_valid());
+        assert!(!invalid_table.is_valid());
+    }
+
+    #[test]
+    fn test_exhale_kernels_conserve_particles() {
+        let cells = BackgroundViralParticleDiseaseSpreader::get_cells_in_exhale_radius(5.0);
+        let kernels = [
+            ExhaleKernel::Uniform,
+            ExhaleKernel::Gaussian { stddev: 2.0 },
+            ExhaleKernel::InverseSquare { min_distance: 1.0 },
+            ExhaleKernel::Cone {
+                half_angle_rad: PI / 4.0,
+            },
+        ];
+
+        for kernel in kernels.iter() {
+            let exhale_weights =
+                BackgroundViralParticleDiseaseSpreader::get_exhale_weights(kernel, &cells);
+            for weights in exhale_weights.iter() {
+                approx::assert_relative_eq!(
+                    weights.iter().sum::<f32>(),
+                    cells.len() as f32,
+                    max_relative = 1e-5
+                );
+            }
+        }
+
+        let uniform_weights =
+            BackgroundViralParticleDiseaseSpreader::get_exhale_weights(&kernels[0], &cells);
+        assert!(uniform_weights[0].iter().all(|w| *w == 1.0));
+
+        let gaussian_weights =
+            BackgroundViralParticleDiseaseSpreader::get_exhale_weights(&kernels[1], &cells);
+        let center_idx = cells.iter().position(|c| *c == (0, 0)).unwrap();
+        let edge_idx = cells.iter().position(|c| *c == (4, 0)).unwrap();
+        assert!(gaussian_weights[0][center_idx] > gaussian_weights[0][edge_idx]);
+    }
+
+    #[test]
+    fn test_cone_kernel_follows_direction_of_travel() {
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 20,
+            right: 20,
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let mut people = random_people(&world_bb, 1, &mut rng);
+        people[0].disease_state = DiseaseState::Symptomatic(0);
+        people[0].position = Position { x: 10.5, y: 10.5 };
+
+        let mut spreader = BackgroundViralParticleDiseaseSpreader::new(
+            world_bb,
+            BackgroundViralParticleParams {
+                exhale_radius: 4.0,
+                exhale_kernel: ExhaleKernel::Cone {
+                    half_angle_rad: PI / 4.0,
+                },
+                decay_rate: 1.0,
+                ventilation: vec![],
+                walls: false,
+                air_movement: None,
+                infection_risk_per_particle: 0.0,
+                infection_model: InfectionModel::PerTick,
+                exhale_multipliers: InfectiousStateValues::default(),
+            },
+            None,
+        );
+
+        // Moving to the left, so only the cells to the left get particles.
+        spreader.spread(1, &mut rng, &mut people);
+        people[0].position.x -= 1.0;
+        spreader.spread(2, &mut rng, &mut people);
+
+        assert!(spreader.get_particles_at(7, 9) > 0.0);
+        assert_eq!(spreader.get_particles_at(11, 9), 0.0);
+        assert_eq!(spreader.get_particles_at(9, 12), 0.0);
+    }
+
+    fn air_movement_spreader(
+        world_bb: BoundingBox,
+        air_movement: AirMovementParams,
+        rng: &mut dyn RngCore,
+    ) -> BackgroundViralParticleDiseaseSpreader {
+        let mut spreader = BackgroundViralParticleDiseaseSpreader::new(
+            world_bb,
+            BackgroundViralParticleParams {
+                exhale_radius: 1.0,
+                exhale_kernel: ExhaleKernel::Uniform,
+                decay_rate: 0.0,
+                ventilation: vec![],
+                walls: false,
+                air_movement: Some(air_movement),
+                infection_risk_per_particle: 0.0,
+                infection_model: InfectionModel::PerTick,
+                exhale_multipliers: InfectiousStateValues::default(),
+            },
+            None,
+        );
+        for val in spreader.background_viral_particles.iter_mut() {
+            if rng.gen_bool(0.2) {
+                *val = rng.gen_range(0.0, 10.0);
+            }
+        }
+        spreader
+    }
+
+    fn total_particles(spreader: &BackgroundViralParticleDiseaseSpreader) -> f64 {
+        spreader
+            .background_viral_particles
+            .iter()
+            .map(|val| *val as f64)
+            .sum()
+    }
+
+    #[test]
+    fn test_air_movement_conserves_particles() {
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 30,
+            right: 50,
+        };
+
+        let air_movements = [
+            AirMovementParams {
+                diffusion_coefficient: 0.25,
+                wind_x: 0.0,
+                wind_y: 0.0,
+            },
+            AirMovementParams {
+                diffusion_coefficient: 0.0,
+                wind_x: 0.7,
+                wind_y: -0.3,
+            },
+            AirMovementParams {
+                diffusion_coefficient: 0.1,
+                wind_x: -0.2,
+                wind_y: 0.5,
+            },
+        ];
+
+        for air_movement in air_movements.iter() {
+            let mut spreader = air_movement_spreader(world_bb, *air_movement, &mut rng);
+            let initial_total = total_particles(&spreader);
+
+            for tick in 1..=200 {
+                spreader.spread(tick, &mut rng, &mut []);
+
+                approx::assert_relative_eq!(
+                    total_particles(&spreader),
+                    initial_total,
+                    max_relative = 1e-4
+                );
+                assert!(spreader
+                    .background_viral_particles
+                    .iter()
+                    .all(|val| *val >= -1e-4));
+            }
+        }
+    }
+
+    #[test]
+    fn test_wind_and_diffusion_move_particles() {
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 20,
+            right: 20,
+        };
+        let mut spreader = BackgroundViralParticleDiseaseSpreader::new(
+            world_bb,
+            BackgroundViralParticleParams {
+                exhale_radius: 1.0,
+                exhale_kernel: ExhaleKernel::Uniform,
+                decay_rate: 0.0,
+                ventilation: vec![],
+                walls: false,
+                air_movement: Some(AirMovementParams {
+                    diffusion_coefficient: 0.1,
+                    wind_x: 0.5,
+                    wind_y: 0.0,
+                }),
+                infection_risk_per_particle: 0.0,
+                infection_model: InfectionModel::PerTick,
+                exhale_multipliers: InfectiousStateValues::default(),
+            },
+            None,
+        );
+        spreader.background_viral_particles[20 * 10 + 5] = 100.0;
+
+        for tick in 1..=10 {
+            spreader.spread(tick, &mut ChaCha8Rng::seed_from_u64(10914), &mut []);
+        }
+
+        // The particles spread out and drift downwind, while staying centered on the same row.
+        assert!(spreader.get_particles_at(5, 10) < 10.0);
+        assert!(spreader.get_particles_at(10, 10) > spreader.get_particles_at(0, 10));
+        approx::assert_relative_eq!(
+            spreader.get_particles_at(10, 9),
+            spreader.get_particles_at(10, 11),
+            max_relative = 1e-4
+        );
     }
 
-    fn get_background_viral_levels(&self) -> &Vec<f32> {
-        &self.background_viral_particles
+    fn simple_groceries_map() -> Result<(Map, BoundingBox)> {
+        let map = crate::v0::maps::loader::load(&MapParams {
+            name: "simple_groceries".to_string(),
+            scale: 10,
+            num_people_per_household: 2,
+        })?;
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 400,
+            right: 600,
+        };
+        Ok((map, world_bb))
+    }
+
+    #[test]
+    fn test_ventilation_overrides_decay_rate() -> Result<()> {
+        let (map, world_bb) = simple_groceries_map()?;
+        let mut params = BackgroundViralParticleParams {
+            exhale_radius: 1.0,
+            exhale_kernel: ExhaleKernel::Uniform,
+            decay_rate: 0.1,
+            ventilation: vec![
+                VentilationParams {
+                    target: VentilationTarget::MapElement(MapElement::Household),
+                    decay_rate: 0.5,
+                    air_exchange_rate: 0.0,
+                },
+                VentilationParams {
+                    target: VentilationTarget::MapElement(MapElement::Store),
+                    decay_rate: 0.2,
+                    air_exchange_rate: 0.5,
+                },
+                VentilationParams {
+                    target: VentilationTarget::Household(0),
+                    decay_rate: 0.0,
+                    air_exchange_rate: 0.0,
+                },
+            ],
+            walls: false,
+            air_movement: None,
+            infection_risk_per_particle: 0.0,
+            infection_model: InfectionModel::PerTick,
+            exhale_multipliers: InfectiousStateValues::default(),
+        };
+        let mut spreader =
+            BackgroundViralParticleDiseaseSpreader::new(world_bb, params.clone(), Some(&map));
+
+        let cells = [
+            map.stores[0].bounds,
+            map.households[0].bounds,
+            map.households[1].bounds,
+            map.roads[0].bounds,
+        ]
+        .iter()
+        .map(|bb| (bb.left, bb.bottom))
+        .collect::<Vec<_>>();
+        for (x, y) in cells.iter() {
+            *BackgroundViralParticleDiseaseSpreader::mut_particles_at(
+                &mut spreader.background_viral_particles,
+                600,
+                *x,
+                *y,
+            ) = 100.0;
+        }
+        spreader.spread(1, &mut ChaCha8Rng::seed_from_u64(10914), &mut []);
+
+        // Later rules take precedence, so the first household is not ventilated at all.
+        let particles = cells
+            .iter()
+            .map(|(x, y)| spreader.get_particles_at(*x, *y))
+            .collect::<Vec<_>>();
+        approx::assert_relative_eq!(particles[0], 40.0);
+        assert_eq!(particles[1], 100.0);
+        approx::assert_relative_eq!(particles[2], 50.0);
+        approx::assert_relative_eq!(particles[3], 90.0);
+
+        // The ventilation targets must exist in the map.
+        let spread_params = DiseaseSpreadParameters::BackgroundViralParticle(params.clone());
+        assert!(validate_spread_parameters(&spread_params, Some(&map)).is_ok());
+        assert!(validate_spread_parameters(&spread_params, None).is_err());
+        params.ventilation[2].target = VentilationTarget::Store(map.stores.len());
+        let spread_params = DiseaseSpreadParameters::BackgroundViralParticle(params);
+        assert!(validate_spread_parameters(&spread_params, Some(&map)).is_err());
+
+        Ok(())
+    }
+
+    fn walls_spreader(
+        map: &Map,
+        world_bb: BoundingBox,
+        walls: bool,
+        air_movement: Option<AirMovementParams>,
+    ) -> BackgroundViralParticleDiseaseSpreader {
+        BackgroundViralParticleDiseaseSpreader::new(
+            world_bb,
+            BackgroundViralParticleParams {
+                exhale_radius: 70.0,
+                exhale_kernel: ExhaleKernel::Uniform,
+                decay_rate: 0.0,
+                ventilation: vec![],
+                walls,
+                air_movement,
+                infection_risk_per_particle: 0.0,
+                infection_model: InfectionModel::PerTick,
+                exhale_multipliers: InfectiousStateValues::default(),
+            },
+            Some(map),
+        )
+    }
+
+    // Total particles in the household in the top left corner of simple_groceries, and in its
+    // neighbor to the right, which is separated from it by background cells.
+    fn household_particles(spreader: &BackgroundViralParticleDiseaseSpreader) -> (f64, f64) {
+        let total = |xs: std::ops::Range<usize>| {
+            let mut total = 0.0;
+            for y in 370..400 {
+                for x in xs.clone() {
+                    total += spreader.get_particles_at(x, y) as f64;
+                }
+            }
+            total
+        };
+        (total(0..30), total(70..100))
+    }
+
+    #[test]
+    fn test_walls_block_exhaled_particles() -> Result<()> {
+        let (map, world_bb) = simple_groceries_map()?;
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let mut people = random_people(&world_bb, 1, &mut rng);
+        people[0].disease_state = DiseaseState::Symptomatic(0);
+        people[0].position = Position { x: 15.5, y: 385.5 };
+
+        let mut spreader = walls_spreader(&map, world_bb, false, None);
+        spreader.spread(1, &mut rng, &mut people);
+        assert!(household_particles(&spreader).1 > 0.0);
+
+        let mut spreader = walls_spreader(&map, world_bb, true, None);
+        spreader.spread(1, &mut rng, &mut people);
+        let (own_particles, neighbor_particles) = household_particles(&spreader);
+        assert!(own_particles > 0.0);
+        assert_eq!(neighbor_particles, 0.0);
+
+        // Particles still leave through the doorway onto the road below, but not through the
+        // household's other walls.
+        assert_eq!(map.get_element(365, 15), MapElement::Road);
+        assert!(spreader.get_particles_at(15, 365) > 0.0);
+        assert_eq!(map.get_element(385, 35), MapElement::Background);
+        assert_eq!(spreader.get_particles_at(35, 385), 0.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walls_block_air_movement() -> Result<()> {
+        let (map, world_bb) = simple_groceries_map()?;
+        let air_movement = AirMovementParams {
+            diffusion_coefficient: 0.2,
+            wind_x: 0.5,
+            wind_y: 0.0,
+        };
+
+        for walls in [false, true] {
+            let mut spreader = walls_spreader(&map, world_bb, walls, Some(air_movement));
+            for y in 370..400 {
+                for x in 0..30 {
+                    spreader.background_viral_particles[600 * y + x] = 100.0;
+                }
+            }
+            let initial_particles = total_particles(&spreader);
+
+            // Too few ticks for the particles to go around through the road and the neighbor's
+            // doorway.
+            let mut rng = ChaCha8Rng::seed_from_u64(10914);
+            for tick in 1..=50 {
+                spreader.spread(tick, &mut rng, &mut []);
+            }
+
+            let (_, neighbor_particles) = household_particles(&spreader);
+            if walls {
+                assert_eq!(neighbor_particles, 0.0);
+            } else {
+                assert!(neighbor_particles > 0.0);
+            }
+            approx::assert_relative_eq!(
+                total_particles(&spreader),
+                initial_particles,
+                max_relative = 1e-4
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dose_response() {
+        let exponential = DoseResponse::Exponential { k: 10.0 };
+        assert_eq!(exponential.probability(0.0), 0.0);
+        approx::assert_relative_eq!(exponential.probability(10.0), 1.0 - (-1.0f32).exp());
+
+        let beta_poisson = DoseResponse::BetaPoisson {
+            alpha: 0.3,
+            n50: 20.0,
+        };
+        approx::assert_relative_eq!(beta_poisson.probability(20.0), 0.5, max_relative = 1e-5);
+        assert!(beta_poisson.probability(1e9) <= 1.0);
+
+        // Surviving each tick's infection probability works out to surviving the total dose.
+        let params = DoseModelParams {
+            breathing_rate: 1.0,
+            dose_decay_rate: 0.0,
+            dose_response: beta_poisson,
+        };
+        let survival = (1.0 - params.infection_probability(0.0, 5.0))
+            * (1.0 - params.infection_probability(5.0, 12.0))
+            * (1.0 - params.infection_probability(12.0, 30.0));
+        approx::assert_relative_eq!(
+            survival,
+            1.0 - beta_poisson.probability(30.0),
+            max_relative = 1e-5
+        );
+        assert_eq!(params.infection_probability(12.0, 5.0), 0.0);
+    }
+
+    #[test]
+    fn test_dose_model_accumulates_dose() {
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 20,
+            right: 20,
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let new_spreader = |k: f32, dose_decay_rate: f32| {
+            let mut spreader = BackgroundViralParticleDiseaseSpreader::new(
+                world_bb,
+                BackgroundViralParticleParams {
+                    exhale_radius: 1.0,
+                    exhale_kernel: ExhaleKernel::Uniform,
+                    decay_rate: 0.0,
+                    ventilation: vec![],
+                    walls: false,
+                    air_movement: None,
+                    infection_risk_per_particle: 0.0,
+                    infection_model: InfectionModel::Dose(DoseModelParams {
+                        breathing_rate: 0.5,
+                        dose_decay_rate,
+                        dose_response: DoseResponse::Exponential { k },
+                    }),
+                    exhale_multipliers: InfectiousStateValues::default(),
+                },
+                None,
+            );
+            spreader.background_viral_particles[20 * 5 + 5] = 2.0;
+            spreader
+        };
+        let mut people = random_people(&world_bb, 1, &mut rng);
+        people[0].disease_state = DiseaseState::Susceptible;
+        people[0].position = Position { x: 5.5, y: 5.5 };
+
+        // With a very high k, the person is never infected and keeps breathing in 1 per tick.
+        let mut spreader = new_spreader(1e9, 0.0);
+        for tick in 1..=4 {
+            assert!(spreader.spread(tick, &mut rng, &mut people).is_empty());
+        }
+        approx::assert_relative_eq!(spreader.doses[0], 4.0);
+
+        // With decay, the dose levels off where the decay balances out the intake.
+        let mut spreader = new_spreader(1e9, 0.5);
+        for tick in 1..=30 {
+            spreader.spread(tick, &mut rng, &mut people);
+        }
+        approx::assert_relative_eq!(spreader.doses[0], 2.0, max_relative = 1e-4);
+
+        // Once the dose has levelled off, each tick's intake still risks infection.
+        let mut spreader = new_spreader(20.0, 0.5);
+        spreader.doses = vec![2.0];
+        let infected =
+            (1..=1000).any(|tick| !spreader.spread(tick, &mut rng, &mut people).is_empty());
+        assert!(infected);
+        people[0].disease_state = DiseaseState::Susceptible;
+
+        // With a very low k, a single tick's dose is enough to be infected.
+        let mut spreader = new_spreader(1e-6, 0.0);
+        let exposures = spreader.spread(1, &mut rng, &mut people);
+        assert_eq!(exposures.len(), 1);
+        assert!(matches!(
+            exposures[0].source,
+            TransmissionSource::ViralParticles { dose, .. } if dose == 1.0
+        ));
+        assert_eq!(spreader.doses[0], 0.0);
+    }
+
+    #[test]
+    fn test_transmission_curves() {
+        let exponential = TransmissionCurve::Exponential {
+            p0: 0.5,
+            length_scale: 2.0,
+        };
+        assert_eq!(exponential.probability(0.0), 0.5);
+        approx::assert_relative_eq!(exponential.probability(2.0), 0.5 / std::f32::consts::E);
+
+        let logistic = TransmissionCurve::Logistic {
+            p_max: 0.8,
+            midpoint: 3.0,
+            steepness: 2.0,
+        };
+        approx::assert_relative_eq!(logistic.probability(3.0), 0.4);
+        assert!(logistic.probability(1.0) > logistic.probability(5.0));
+
+        let table = TransmissionCurve::Table(vec![(1.0, 0.6), (3.0, 0.2), (5.0, 0.0)]);
+        assert!(table.is_valid());
+        assert_eq!(table.probability(0.5), 0.6);
+        approx::assert_relative_eq!(table.probability(2.0), 0.4);
+        assert_eq!(table.probability(5.0), 0.0);
+        assert_eq!(table.probability(9.0), 0.0);
+        assert!(!TransmissionCurve::Table(vec![(3.0, 0.2), (1.0, 0.6)]).is_valid());
+    }
+
+    #[test]
+    fn test_probabilistic_radius_falls_off_with_distance() {
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 100,
+            right: 100,
+        };
+        let params = ProbabilisticRadiusParams {
+            max_distance: 10.0,
+            curve: TransmissionCurve::Exponential {
+                p0: 0.5,
+                length_scale: 3.0,
+            },
+            infectiousness_multipliers: InfectiousStateValues::default(),
+        };
+
+        // One infectious person in the middle, with rings of susceptible people around them.
+        let distances = [1.0, 8.0, 12.0];
+        let num_people_per_ring = 300;
+        let new_people = |rng: &mut dyn RngCore| {
+            let mut people = random_people(&world_bb, 1 + 3 * num_people_per_ring, rng);
+            for (idx, p) in people.iter_mut().enumerate() {
+                if idx == 0 {
+                    p.disease_state = DiseaseState::Symptomatic(0);
+                    p.position = Position { x: 50.0, y: 50.0 };
+                    continue;
+                }
+                let distance = distances[(idx - 1) / num_people_per_ring];
+                let angle = idx as f32;
+                p.disease_state = DiseaseState::Susceptible;
+                p.position = Position {
+                    x: 50.0 + distance * angle.cos(),
+                    y: 50.0 + distance * angle.sin(),
+                };
+            }
+            for p in people.iter_mut() {
+                p.last_position = p.position.clone();
+            }
+            people
+        };
+        let count_by_ring = |exposures: &[Exposure]| {
+            let mut counts = [0; 3];
+            for exposure in exposures {
+                assert!(matches!(
+                    exposure.source,
+                    TransmissionSource::CloseContact { infector: 0 }
+                ));
+                counts[(exposure.infectee - 1) / num_people_per_ring] += 1;
+            }
+            counts
+        };
+
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let mut spreader = ProbabilisticRadiusDiseaseSpreader::new(world_bb, params.clone());
+        let mut people = new_people(&mut rng);
+        let counts = count_by_ring(&spreader.spread(1, &mut rng, &mut people));
+        assert!(counts[0] > 4 * counts[1]);
+        assert!(counts[1] > 0);
+        assert_eq!(counts[2], 0);
+
+        // A respirator on the infectious person cuts down on exposures.
+        let mut people = new_people(&mut rng);
+        people[0].mask = Mask::Respirator;
+        people[0].mask_filtration = MaskFiltrationTable::default().get(Mask::Respirator, 1.0);
+        let masked_counts = count_by_ring(&spreader.spread(1, &mut rng, &mut people));
+        assert!(masked_counts[0] * 4 < counts[0]);
+    }
+
+    #[test]
+    fn test_fomites_contaminate_stores() -> Result<()> {
+        let (map, world_bb) = simple_groceries_map()?;
+        let params = FomiteParams {
+            deposit_rate: 4.0,
+            deposit_multipliers: InfectiousStateValues::default(),
+            pickup_fraction: 0.5,
+            infection_risk_per_unit: 0.0,
+            decay_rate: 0.5,
+            cleaning: Some(CleaningParams {
+                period_ticks: 3,
+                efficacy: 1.0,
+            }),
+        };
+        let mut spreader = FomiteDiseaseSpreader::new(world_bb, params, Some(&map));
+        let store_bb = map.stores[0].bounds;
+        let store_cell = (store_bb.left + 1, store_bb.bottom + 1);
+        let contamination_at = |spreader: &FomiteDiseaseSpreader, cell: (usize, usize)| {
+            spreader.contamination[600 * cell.1 + cell.0]
+        };
+
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let mut people = random_people(&world_bb, 3, &mut rng);
+        people[0].disease_state = DiseaseState::Symptomatic(0);
+        people[0].position = Position {
+            x: store_cell.0 as f32 + 0.5,
+            y: store_cell.1 as f32 + 0.5,
+        };
+        // Infectious people outside of stores do not contaminate anything.
+        people[1].disease_state = DiseaseState::Symptomatic(0);
+        people[1].position = Position { x: 15.5, y: 385.5 };
+        people[2].disease_state = DiseaseState::Susceptible;
+        people[2].position = people[0].position.clone();
+
+        spreader.spread(1, &mut rng, &mut people);
+        assert_eq!(contamination_at(&spreader, store_cell), 4.0);
+        assert_eq!(contamination_at(&spreader, (15, 385)), 0.0);
+
+        // Half decays, the susceptible person picks up half of the rest, and more is deposited.
+        let exposures = spreader.spread(2, &mut rng, &mut people);
+        assert!(exposures.is_empty());
+        assert_eq!(contamination_at(&spreader, store_cell), 5.0);
+
+        // Cleaning removes everything before the next deposit.
+        spreader.spread(3, &mut rng, &mut people);
+        assert_eq!(contamination_at(&spreader, store_cell), 4.0);
+
+        // With a high enough risk, picking up contamination exposes the susceptible person.
+        spreader.params.infection_risk_per_unit = 1.0;
+        let exposures = spreader.spread(4, &mut rng, &mut people);
+        assert_eq!(exposures.len(), 1);
+        assert_eq!(exposures[0].infectee, 2);
+        assert!(matches!(
+            exposures[0].source,
+            TransmissionSource::Fomite { dose, cell } if dose == 1.0 && cell == store_cell
+        ));
+
+        // New parameters keep the contamination that is already on the surfaces.
+        let contamination = contamination_at(&spreader, store_cell);
+        assert!(spreader.set_parameters(
+            &DiseaseSpreadParameters::Fomite(FomiteParams {
+                decay_rate: 0.1,
+                ..params
+            }),
+            Some(&map)
+        ));
+        assert_eq!(spreader.params.decay_rate, 0.1);
+        assert_eq!(contamination_at(&spreader, store_cell), contamination);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_runs_every_route() {
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 100,
+            right: 100,
+        };
+        let infection_radius = DiseaseSpreadParameters::InfectionRadius(InfectionRadiusParams {
+            radius: 3.0,
+            radius_multipliers: InfectiousStateValues::default(),
+        });
+        let background_viral_particle =
+            DiseaseSpreadParameters::BackgroundViralParticle(BackgroundViralParticleParams {
+                exhale_radius: 9.0,
+                exhale_kernel: ExhaleKernel::Uniform,
+                decay_rate: 0.05,
+                ventilation: vec![],
+                walls: false,
+                air_movement: None,
+                infection_risk_per_particle: 0.01,
+                infection_model: InfectionModel::PerTick,
+                exhale_multipliers: InfectiousStateValues::default(),
+            });
+        let params = DiseaseSpreadParameters::Composite(vec![
+            infection_radius.clone(),
+            background_viral_particle.clone(),
+        ]);
+        assert!(params.is_valid(None));
+        assert!(!DiseaseSpreadParameters::Composite(vec![]).is_valid(None));
+
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let mut spreader = new_disease_spreader(world_bb, &params, None);
+        let mut people = random_people(&world_bb, 500, &mut rng);
+        let mut routes = vec![];
+        for tick in 1..=20 {
+            routes.extend(
+                spreader
+                    .spread(tick, &mut rng, &mut people)
+                    .into_iter()
+                    .map(|exposure| exposure.route),
+            );
+        }
+        assert!(routes.contains(&Some(0)));
+        assert!(routes.contains(&Some(1)));
+        assert!(routes.iter().all(|route| route.is_some()));
+
+        // The particles from the second route are visible through the composite.
+        let levels = spreader.get_background_viral_levels().unwrap().clone();
+        assert!(levels.iter().any(|val| *val > 0.0));
+        let restored = spreader.checkpoint().restore();
+        assert_eq!(restored.get_background_viral_levels(), Some(&levels));
+
+        // Routes can be updated in place, but not swapped for a different spread model.
+        assert!(spreader.set_parameters(&params, None));
+        let swapped =
+            DiseaseSpreadParameters::Composite(vec![background_viral_particle, infection_radius]);
+        assert!(!spreader.set_parameters(&swapped, None));
+    }
+
+    #[test]
+    fn test_household_spread_stays_within_households() -> Result<()> {
+        let (map, world_bb) = simple_groceries_map()?;
+        let mut params = HouseholdTransmissionParams {
+            hazard_per_tick: 1.0,
+            infectiousness_multipliers: InfectiousStateValues::default(),
+            only_at_home: false,
+        };
+        let spread_params = DiseaseSpreadParameters::Household(params);
+        assert!(spread_params.is_valid(Some(&map)));
+        assert!(!spread_params.is_valid(None));
+
+        let home = Position {
+            x: map.households[0].bounds.left as f32 + 0.5,
+            y: map.households[0].bounds.bottom as f32 + 0.5,
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let new_people = |rng: &mut ChaCha8Rng| {
+            let mut people = random_people(&world_bb, 4, rng);
+            for (p, household_idx) in people.iter_mut().zip(vec![0, 0, 1, 0]) {
+                p.disease_state = DiseaseState::Susceptible;
+                p.household_idx = household_idx;
+                p.position = home.clone();
+            }
+            people[0].disease_state = DiseaseState::Symptomatic(0);
+            // Person 3 is away from home.
+            people[3].position = Position { x: 300.5, y: 200.5 };
+            people
+        };
+
+        let mut spreader = new_disease_spreader(world_bb, &spread_params, Some(&map));
+        let mut people = new_people(&mut rng);
+        let exposures = spreader.spread(1, &mut rng, &mut people);
+        let infectees = exposures.iter().map(|e| e.infectee).collect::<Vec<_>>();
+        assert_eq!(infectees, vec![1, 3]);
+        assert!(exposures
+            .iter()
+            .all(|e| e.source == TransmissionSource::Household { infector: 0 }));
+
+        // With only_at_home, the member who is away from home is not exposed.
+        params.only_at_home = true;
+        assert!(spreader.set_parameters(&DiseaseSpreadParameters::Household(params), Some(&map)));
+        let mut people = new_people(&mut rng);
+        let exposures = spreader
+            .checkpoint()
+            .restore()
+            .spread(1, &mut rng, &mut people);
+        let infectees = exposures.iter().map(|e| e.infectee).collect::<Vec<_>>();
+        assert_eq!(infectees, vec![1]);
+
+        Ok(())
     }
 }
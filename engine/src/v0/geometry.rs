@@ -10,7 +10,7 @@ use rand::{Rng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::f32::consts::PI;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub(crate) struct Position {
     pub(crate) x: f32,
     pub(crate) y: f32,
@@ -64,9 +64,14 @@ impl Position {
         ((self.x - other.x) * (self.x - other.x) + (self.y - other.y) * (self.y - other.y)).sqrt()
     }
 
-    pub(crate) fn advance(&mut self, direction_rad: &mut f32, bounding_box: &BoundingBox) {
-        self.x += direction_rad.cos();
-        self.y -= direction_rad.sin();
+    pub(crate) fn advance(
+        &mut self,
+        direction_rad: &mut f32,
+        distance: f32,
+        bounding_box: &BoundingBox,
+    ) {
+        self.x += distance * direction_rad.cos();
+        self.y -= distance * direction_rad.sin();
 
         let top_boundary = bounding_box.top as f32;
         let left_boundary = bounding_box.left as f32;
@@ -99,7 +104,33 @@ impl Position {
     }
 }
 
-fn normalize_angle(t: f32) -> f32 {
+// Closest that two points get to each other while they move in straight lines over the same
+// period, one from a_from to a_to and the other from b_from to b_to.
+pub(crate) fn closest_approach(
+    a_from: &Position,
+    a_to: &Position,
+    b_from: &Position,
+    b_to: &Position,
+) -> f32 {
+    // Offset of a from b at the start, and how much it changes over the period.
+    let dx = a_from.x - b_from.x;
+    let dy = a_from.y - b_from.y;
+    let vx = (a_to.x - a_from.x) - (b_to.x - b_from.x);
+    let vy = (a_to.y - a_from.y) - (b_to.y - b_from.y);
+
+    let speed_sq = vx * vx + vy * vy;
+    let t = if speed_sq > 0.0 {
+        (-(dx * vx + dy * vy) / speed_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    let x = dx + t * vx;
+    let y = dy + t * vy;
+    (x * x + y * y).sqrt()
+}
+
+pub(crate) fn normalize_angle(t: f32) -> f32 {
     let rem = t % (2.0 * PI);
     if rem < 0. {
         2.0 * PI + rem
@@ -145,6 +176,31 @@ mod tests {
         approx::assert_ulps_eq!(p2.distance(&p3), 1.0);
     }
 
+    #[test]
+    fn test_closest_approach() {
+        let origin = Position { x: 0.0, y: 0.0 };
+
+        // Standing still.
+        let p = Position { x: 3.0, y: 4.0 };
+        approx::assert_ulps_eq!(closest_approach(&p, &p, &origin, &origin), 5.0);
+
+        // Walking past each other along the same line.
+        let west = Position { x: -3.0, y: 0.0 };
+        let east = Position { x: 3.0, y: 0.0 };
+        approx::assert_ulps_eq!(closest_approach(&west, &east, &east, &west), 0.0);
+
+        // Walking side by side.
+        let west_above = Position { x: -3.0, y: 1.0 };
+        let east_above = Position { x: 3.0, y: 1.0 };
+        approx::assert_ulps_eq!(
+            closest_approach(&west, &east, &west_above, &east_above),
+            1.0
+        );
+
+        // Walking away from each other.
+        approx::assert_ulps_eq!(closest_approach(&origin, &west, &p, &p), 5.0);
+    }
+
     struct PositionAndDirection {
         pub(crate) position: Position,
         pub(crate) direction_rad: f32,
@@ -152,7 +208,8 @@ mod tests {
 
     impl PositionAndDirection {
         fn advance(&mut self, world_bb: &BoundingBox) {
-            self.position.advance(&mut self.direction_rad, world_bb);
+            self.position
+                .advance(&mut self.direction_rad, 1.0, world_bb);
         }
     }
 
@@ -0,0 +1,260 @@
+// Applies interventions that change the world's parameters while the simulation runs.
+
+use crate::v0::config::{
+    BehaviorParameters, InterventionChange, ScheduledIntervention, WorldConfig,
+};
+use crate::v0::core::World;
+use crate::v0::disease_spread::{new_disease_spreader, validate_spread_parameters};
+use crate::v0::maps::Map;
+use crate::v0::person_behavior::validate_shopper_params;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct InterventionRecord {
+    pub tick: usize,
+    pub change: InterventionChange,
+}
+
+pub(crate) fn validate_interventions(config: &WorldConfig, map: Option<&Map>) -> Result<()> {
+    validate_changes(
+        config,
+        map,
+        config
+            .interventions
+            .iter()
+            .map(|intervention| &intervention.change),
+    )
+}
+
+// Orders the configured interventions by tick. Interventions on the same tick are applied in the
+// configured order.
+pub(crate) fn schedule_interventions(config: &WorldConfig) -> Vec<ScheduledIntervention> {
+    let mut interventions = config.interventions.clone();
+    interventions.sort_by_key(|intervention| intervention.tick);
+    interventions
+}
+
+pub(crate) fn validate_changes<'a, I>(
+    config: &WorldConfig,
+    map: Option<&Map>,
+    changes: I,
+) -> Result<()>
+where
+    I: Iterator<Item = &'a InterventionChange>,
+{
+    for change in changes {
+        match change {
+            InterventionChange::SetMaskFractions(fractions) => {
+                if !fractions.is_valid() {
+                    return Err(anyhow!("invalid mask parameters: {:?}", change));
+                }
+            }
+            InterventionChange::SetSpreadParameters(params) => {
+                validate_spread_parameters(params, map)?;
+            }
+            InterventionChange::SetShopperParams(params) => {
+                if let BehaviorParameters::BrownianMotion = config.behavior_parameters {
+                    return Err(anyhow!(
+                        "intervention is only valid for shopper behavior: {:?}",
+                        change
+                    ));
+                }
+                validate_shopper_params(params, config)?;
+            }
+            InterventionChange::SetHouseholdIsolation { household_idxs, .. } => {
+                if let BehaviorParameters::BrownianMotion = config.behavior_parameters {
+                    return Err(anyhow!(
+                        "intervention is only valid for shopper behavior: {:?}",
+                        change
+                    ));
+                }
+
+                let num_households = map.map_or(0, |m| m.households.len());
+                if let Some(idx) = household_idxs.iter().find(|idx| **idx >= num_households) {
+                    return Err(anyhow!("invalid household index {} in {:?}", idx, change));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl World {
+    pub(crate) fn apply_scheduled_interventions(&mut self) {
+        while self.next_intervention_idx < self.scheduled_interventions.len() {
+            let intervention = &self.scheduled_interventions[self.next_intervention_idx];
+            if intervention.tick > self.tick {
+                break;
+            }
+
+            let change = intervention.change.clone();
+            self.apply_intervention(change);
+            self.next_intervention_idx += 1;
+        }
+    }
+
+    pub(crate) fn apply_intervention(&mut self, change: InterventionChange) {
+        match &change {
+            InterventionChange::SetMaskFractions(fractions) => {
+                let params = &mut self.config.misc_parameters;
+                params.set_mask_fractions(fractions);
+
+                // People keep how well their masks fit.
+                let masks = Self::assign_masks(self.people.len(), params, &mut self.rng);
+                for (person, mask) in self.people.iter_mut().zip(masks) {
+                    person.mask = mask;
+                    person.mask_filtration = params.mask_filtration.get(mask, person.mask_fit);
+                }
+            }
+            InterventionChange::SetShopperParams(params) => {
+                self.config.behavior_parameters = BehaviorParameters::Shopper(params.clone());
+                self.person_behavior
+                    .set_shopper_params(params.clone(), &mut self.rng);
+            }
+            InterventionChange::SetSpreadParameters(params) => {
+                self.config.disease_parameters.spread_parameters = params.clone();
+
+                // Switching to a different spread model starts it from a clean slate.
+                if !self
+                    .disease_spreader
+                    .set_parameters(params, self.map.as_ref())
+                {
+                    self.disease_spreader =
+                        new_disease_spreader(self.config.bounding_box, params, self.map.as_ref());
+                }
+            }
+            InterventionChange::SetHouseholdIsolation {
+                household_idxs,
+                isolated,
+            } => {
+                for household_idx in household_idxs.iter() {
+                    self.person_behavior
+                        .set_household_isolation(*household_idx, *isolated);
+                }
+            }
+        }
+
+        self.intervention_records.push(InterventionRecord {
+            tick: self.tick,
+            change,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::city::config::DistributionParams;
+    use crate::v0::checkpoint::SeededRng;
+    use crate::v0::config::{MaskFiltration, MaskFiltrationTable, MaskFractions};
+    use crate::v0::test_util;
+    use crate::v0::types::Mask;
+
+    fn shopping_config(interventions: Vec<ScheduledIntervention>) -> WorldConfig {
+        WorldConfig {
+            interventions,
+            ..test_util::shopping_config()
+        }
+    }
+
+    #[test]
+    fn test_scheduled_interventions_fire_in_order() -> Result<()> {
+        let mask_mandate = InterventionChange::SetMaskFractions(MaskFractions {
+            fraction_mask: 0.5,
+            fraction_n95_mask: 0.5,
+            ..MaskFractions::default()
+        });
+        let isolation = InterventionChange::SetHouseholdIsolation {
+            household_idxs: vec![0, 1],
+            isolated: true,
+        };
+        let config = shopping_config(vec![
+            ScheduledIntervention {
+                tick: 20,
+                change: isolation,
+            },
+            ScheduledIntervention {
+                tick: 10,
+                change: mask_mandate,
+            },
+        ]);
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+        // The config is kept as given.
+        assert_eq!(world.config.interventions[0].tick, 20);
+
+        for _ in 0..9 {
+            world.step();
+        }
+        assert!(world.intervention_records.is_empty());
+        assert!(world.people.iter().all(|p| matches!(p.mask, Mask::None)));
+
+        world.step();
+        assert_eq!(world.intervention_records.len(), 1);
+        assert_eq!(world.intervention_records[0].tick, 10);
+        assert!(world.people.iter().all(|p| !matches!(p.mask, Mask::None)));
+
+        for _ in 0..20 {
+            world.step();
+        }
+        assert_eq!(world.intervention_records.len(), 2);
+        assert_eq!(world.intervention_records[1].tick, 20);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mask_mandate_keeps_mask_filtration_and_fit() -> Result<()> {
+        let n95 = MaskFiltration {
+            inhale: 0.05,
+            exhale: 0.1,
+        };
+        let mut config = shopping_config(vec![ScheduledIntervention {
+            tick: 1,
+            change: InterventionChange::SetMaskFractions(MaskFractions {
+                fraction_n95_mask: 1.0,
+                ..MaskFractions::default()
+            }),
+        }]);
+        config.misc_parameters.mask_filtration = MaskFiltrationTable {
+            n95,
+            ..MaskFiltrationTable::default()
+        };
+        config.misc_parameters.mask_fit = Some(DistributionParams::Uniform(0.5, 1.0));
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+        let mask_fits = world.people.iter().map(|p| p.mask_fit).collect::<Vec<_>>();
+
+        world.step();
+        assert_eq!(world.config.misc_parameters.mask_filtration.n95, n95);
+        assert!(world.config.misc_parameters.mask_fit.is_some());
+        for (p, mask_fit) in world.people.iter().zip(mask_fits) {
+            assert!(matches!(p.mask, Mask::N95));
+            assert_eq!(p.mask_fit, mask_fit);
+            assert_eq!(
+                p.mask_filtration,
+                MaskFiltrationTable {
+                    n95,
+                    ..MaskFiltrationTable::default()
+                }
+                .get(Mask::N95, mask_fit)
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shopper_interventions_require_shopper_behavior() {
+        let mut config = shopping_config(vec![ScheduledIntervention {
+            tick: 10,
+            change: InterventionChange::SetHouseholdIsolation {
+                household_idxs: vec![0],
+                isolated: true,
+            },
+        }]);
+        config.behavior_parameters = BehaviorParameters::BrownianMotion;
+
+        assert!(World::new(Box::new(SeededRng::new(10914)), config).is_err());
+    }
+}
@@ -4,11 +4,15 @@ use crate::v0::geometry::BoundingBox;
 use anyhow::{anyhow, Result};
 use pathfinding::directed::astar::astar;
 use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 
 pub mod loader;
 
+// Cached path from a (household, store) pair.
+pub(crate) type PathCacheEntry = ((usize, usize), Vec<(u16, u16)>);
+
 pub(crate) struct Household {
     pub(crate) bounds: BoundingBox,
 
@@ -39,12 +43,22 @@ pub struct Map {
     household_to_store_path_cache: RefCell<HashMap<(usize, usize), Vec<(u16, u16)>>>,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub(crate) enum MapElement {
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum MapElement {
+    #[serde(rename = "background")]
     Background,
+    #[serde(rename = "household")]
     Household,
+    #[serde(rename = "road")]
     Road,
+    #[serde(rename = "store")]
     Store,
+    #[serde(rename = "wall")]
+    Wall,
+
+    // Not part of any map: the queue outside a full store, on the road next to its entrance.
+    #[serde(rename = "store_queue")]
+    StoreQueue,
 }
 
 impl Map {
@@ -59,6 +73,7 @@ impl Map {
                         'H' => Ok(MapElement::Household),
                         'R' => Ok(MapElement::Road),
                         'S' => Ok(MapElement::Store),
+                        'W' => Ok(MapElement::Wall),
                         _ => Err(anyhow!("invalid char in line: <{}>", c)),
                     })
                     .collect::<Result<Vec<_>>>()
@@ -229,7 +244,10 @@ impl Map {
         })
     }
 
-    fn find_bb_road_intersection(&self, bb: &BoundingBox) -> Vec<((u16, u16), (u16, u16))> {
+    pub(crate) fn find_bb_road_intersection(
+        &self,
+        bb: &BoundingBox,
+    ) -> Vec<((u16, u16), (u16, u16))> {
         // Returns a list of pairs of points, such that
         //   the first point of the pair is inside the bounding box
         //   the second point of the pair is on the road
@@ -378,6 +396,23 @@ impl Map {
         Ok(path.into_iter().rev().collect())
     }
 
+    pub(crate) fn get_path_cache(&self) -> Vec<PathCacheEntry> {
+        let mut entries = self
+            .household_to_store_path_cache
+            .borrow()
+            .iter()
+            .map(|(key, path)| (*key, path.clone()))
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries
+    }
+
+    pub(crate) fn restore_path_cache(&self, entries: Vec<PathCacheEntry>) {
+        self.household_to_store_path_cache
+            .borrow_mut()
+            .extend(entries);
+    }
+
     pub(crate) fn get_element(&self, row: usize, col: usize) -> MapElement {
         self.elements[row / self.scale_factor as usize][col / self.scale_factor as usize]
     }
@@ -5,6 +5,7 @@
 // H: Household
 // R: Road
 // S: Store
+// W: Wall
 pub(crate) static MAP_ASCII_STR: &str = "\
 HHH.R..HHH...HHH...HHH...HHH....HHH...HHH...HHH...HHH..R.HHH
 HHH.R..HHH...HHH...HHH...HHH....HHH...HHH...HHH...HHH..R.HHH
@@ -1,11 +1,19 @@
 extern crate wasm_bindgen;
 
+pub mod checkpoint;
 pub mod config;
 pub(crate) mod core;
 mod disease_spread;
 pub mod geometry;
+pub mod interventions;
 pub mod maps;
 mod person_behavior;
+mod spatial_index;
+#[cfg(test)]
+mod test_util;
+pub mod transmission;
+pub mod triggers;
 pub mod types;
 mod utils;
+mod vaccination;
 pub mod wasm_view;
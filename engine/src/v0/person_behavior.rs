@@ -1,17 +1,25 @@
-use crate::v0::config::ShopperParams;
-use crate::v0::core::Person;
-use crate::v0::geometry::{BoundingBox, Position};
+use crate::v0::config::{
+    ClockParams, DistancingParams, ElementSpeedMultiplier, IsolationShopper, OpeningHours,
+    ShopperParams, SpecialHoursShoppers, StoreChoice, WalkingSpeedParams, WorldConfig,
+};
+use crate::v0::core::{DiseaseState, Person};
+use crate::v0::geometry::{normalize_angle, BoundingBox, Position};
 use crate::v0::maps::MapElement;
+use crate::v0::spatial_index::UniformGrid;
 use crate::v0::utils::random_bool_vec;
 use crate::v0::{maps, wasm_view};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::f32::consts::PI;
 use std::iter::Iterator;
 
 pub(crate) trait PersonBehavior {
     fn update_positions(
         &mut self,
+        tick: usize,
         people: &mut [Person],
         map: &mut Option<maps::Map>,
         rng: &mut dyn RngCore,
@@ -20,25 +28,221 @@ pub(crate) trait PersonBehavior {
     fn update_household_state(&self, _idx: usize, _state: &mut wasm_view::HouseholdState) {
         unimplemented!()
     }
+
+    fn checkpoint(&self) -> PersonBehaviorCheckpoint;
+
+    // Behaviors without shoppers ignore shopper interventions, which are rejected by validation.
+    fn set_shopper_params(&mut self, _params: ShopperParams, _rng: &mut dyn RngCore) {}
+
+    fn set_household_isolation(&mut self, _household_idx: usize, _isolated: bool) {}
+
+    // Number of shopping trips that arrived at each store, if the behavior has stores.
+    fn get_store_visits(&self) -> Option<&[usize]> {
+        None
+    }
+
+    // Queueing outside each store, if the behavior has stores.
+    fn get_store_queue_stats(&self) -> Option<&[wasm_view::StoreQueueStats]> {
+        None
+    }
+
+    // Whether the person is waiting in the queue outside a store.
+    fn is_queueing(&self, _idx: usize) -> bool {
+        false
+    }
+}
+
+pub(crate) fn validate_shopper_params(params: &ShopperParams, config: &WorldConfig) -> Result<()> {
+    if !(0.0..=1.0).contains(&params.fraction_senior_households) {
+        return Err(anyhow!("invalid shopper parameters: {:?}", params));
+    }
+    // Without symptom parameters, every infectious person is symptomatic, so everyone would
+    // isolate as soon as they became infectious.
+    if let Some(isolation) = &params.symptomatic_isolation {
+        if config.disease_parameters.symptoms.is_none() {
+            return Err(anyhow!(
+                "symptomatic isolation requires symptom parameters: {:?}",
+                isolation
+            ));
+        }
+    }
+    if let Some(hours) = &params.opening_hours {
+        let ticks_per_day = match &config.clock {
+            Some(clock) => clock.ticks_per_day,
+            None => return Err(anyhow!("opening hours require a clock: {:?}", hours)),
+        };
+        let windows = std::iter::once((hours.open_time_of_day, hours.close_time_of_day)).chain(
+            hours
+                .special_hours
+                .iter()
+                .map(|window| (window.start_time_of_day, window.end_time_of_day)),
+        );
+        for (start, end) in windows {
+            if start >= ticks_per_day || end >= ticks_per_day {
+                return Err(anyhow!("invalid opening hours: {:?}", hours));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Saved state of a person behavior, used to restore it from a checkpoint.
+#[derive(Deserialize, Serialize)]
+pub(crate) enum PersonBehaviorCheckpoint {
+    BrownianMotion(BrownianMotionBehavior),
+    Shopper(ShopperBehavior),
+}
+
+impl PersonBehaviorCheckpoint {
+    pub(crate) fn restore(self) -> Box<dyn PersonBehavior> {
+        match self {
+            PersonBehaviorCheckpoint::BrownianMotion(behavior) => Box::new(behavior),
+            PersonBehaviorCheckpoint::Shopper(behavior) => Box::new(behavior),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Walking speeds
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Deserialize, Serialize)]
+struct WalkingSpeeds {
+    per_person_speeds: Vec<f32>,
+    element_multipliers: Vec<ElementSpeedMultiplier>,
+}
+
+impl WalkingSpeeds {
+    fn new(params: Option<&WalkingSpeedParams>, num_people: usize, rng: &mut dyn RngCore) -> Self {
+        match params {
+            Some(params) => WalkingSpeeds {
+                per_person_speeds: (0..num_people)
+                    .map(|_| params.speed.sample(rng) as f32)
+                    .collect(),
+                element_multipliers: params.element_multipliers.clone(),
+            },
+            None => WalkingSpeeds {
+                per_person_speeds: vec![1.0; num_people],
+                element_multipliers: vec![ElementSpeedMultiplier {
+                    element: MapElement::Road,
+                    multiplier: 3.0,
+                }],
+            },
+        }
+    }
+
+    // Distance the person walks in a tick on the map element.
+    fn get(&self, idx: usize, element: MapElement) -> f32 {
+        let multiplier = self
+            .element_multipliers
+            .iter()
+            .find(|element_multiplier| element_multiplier.element == element)
+            .map_or(1.0, |element_multiplier| element_multiplier.multiplier);
+
+        self.per_person_speeds[idx] * multiplier
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Distancing
+///////////////////////////////////////////////////////////////////////////////
+
+impl DistancingParams {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.personal_space_radius > 0.0
+            && self.repulsion_strength >= 0.0
+            && (0.0..=1.0).contains(&self.fraction_compliant)
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct Distancing {
+    params: DistancingParams,
+    world_bounding_box: BoundingBox,
+
+    // Whether each person keeps their distance from others.
+    compliant: Vec<bool>,
+}
+
+impl Distancing {
+    fn new(
+        params: DistancingParams,
+        world_bounding_box: BoundingBox,
+        num_people: usize,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        Distancing {
+            params,
+            world_bounding_box,
+            compliant: random_bool_vec(num_people, params.fraction_compliant, rng),
+        }
+    }
+
+    // Turns the heading of each compliant person away from everyone inside their personal space.
+    // People without a heading are not steered, but others still keep their distance from them.
+    fn steer(&self, people: &[Person], directions_rad: &mut [Option<f32>]) {
+        let radius = self.params.personal_space_radius;
+        let grid = UniformGrid::new(
+            &self.world_bounding_box,
+            radius,
+            people.iter().enumerate().map(|(idx, p)| (idx, &p.position)),
+        );
+
+        for (idx, p) in people.iter().enumerate() {
+            let direction_rad = match &mut directions_rad[idx] {
+                Some(direction_rad) if self.compliant[idx] => direction_rad,
+                _ => continue,
+            };
+
+            let mut repulsion_x = 0.0;
+            let mut repulsion_y = 0.0;
+            for other_idx in grid.query(&p.position, radius) {
+                let other = &people[other_idx].position;
+                let distance = p.position.distance(other);
+                if other_idx == idx || distance == 0.0 || distance >= radius {
+                    continue;
+                }
+
+                let weight = self.params.repulsion_strength * (1.0 - distance / radius) / distance;
+                repulsion_x += (p.position.x - other.x) * weight;
+                repulsion_y += (p.position.y - other.y) * weight;
+            }
+            if repulsion_x == 0.0 && repulsion_y == 0.0 {
+                continue;
+            }
+
+            // A heading points along (cos, -sin), see Position::advance.
+            let x = direction_rad.cos() + repulsion_x;
+            let y = -direction_rad.sin() + repulsion_y;
+            *direction_rad = normalize_angle((-y).atan2(x));
+        }
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
 // Brownian motion behaviors
 ///////////////////////////////////////////////////////////////////////////////
 
+#[derive(Clone, Deserialize, Serialize)]
 struct BrownianMotionPersonState {
     direction_rad: f32,
 }
 
+#[derive(Clone, Deserialize, Serialize)]
 pub(crate) struct BrownianMotionBehavior {
     world_bounding_box: BoundingBox,
     per_person_states: Vec<BrownianMotionPersonState>,
+    walking_speeds: WalkingSpeeds,
+    distancing: Option<Distancing>,
 }
 
 impl BrownianMotionBehavior {
     pub(crate) fn new(
         world_bounding_box: BoundingBox,
         num_people: usize,
+        distancing: Option<DistancingParams>,
+        walking_speed: Option<&WalkingSpeedParams>,
         rng: &mut dyn RngCore,
     ) -> Self {
         let per_person_states = (0..num_people)
@@ -50,6 +254,9 @@ impl BrownianMotionBehavior {
         BrownianMotionBehavior {
             world_bounding_box,
             per_person_states,
+            walking_speeds: WalkingSpeeds::new(walking_speed, num_people, rng),
+            distancing: distancing
+                .map(|params| Distancing::new(params, world_bounding_box, num_people, rng)),
         }
     }
 }
@@ -57,43 +264,77 @@ impl BrownianMotionBehavior {
 impl PersonBehavior for BrownianMotionBehavior {
     fn update_positions(
         &mut self,
+        _: usize,
         people: &mut [Person],
         _: &mut Option<maps::Map>,
         _: &mut dyn RngCore,
     ) {
+        if let Some(distancing) = &self.distancing {
+            let mut directions_rad = self
+                .per_person_states
+                .iter()
+                .map(|state| Some(state.direction_rad))
+                .collect::<Vec<_>>();
+            distancing.steer(people, &mut directions_rad);
+            for (state, direction_rad) in self.per_person_states.iter_mut().zip(directions_rad) {
+                if let Some(direction_rad) = direction_rad {
+                    state.direction_rad = direction_rad;
+                }
+            }
+        }
+
         for (idx, person) in people.iter_mut().enumerate() {
             let state = &mut self.per_person_states[idx];
 
-            person
-                .position
-                .advance(&mut state.direction_rad, &self.world_bounding_box)
+            person.position.advance(
+                &mut state.direction_rad,
+                self.walking_speeds.get(idx, MapElement::Background),
+                &self.world_bounding_box,
+            )
         }
     }
+
+    fn checkpoint(&self) -> PersonBehaviorCheckpoint {
+        PersonBehaviorCheckpoint::BrownianMotion(self.clone())
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
 // Shopper behaviors
 ///////////////////////////////////////////////////////////////////////////////
 
+#[derive(Clone, Deserialize, Serialize)]
 enum ShopperState {
     AtHome {
         direction_rad: f32,
     },
     GoingToStore {
         path_idx: usize,
+        // How far along the path the person is from path[path_idx] towards the next point.
+        #[serde(default)]
+        path_offset: f32,
         path: Vec<(u16, u16)>,
     },
+    Queueing {
+        wait_ticks: usize,
+    },
     Shopping {
         direction_rad: f32,
         shopping_duration_ticks: usize,
     },
     ReturningHome {
         path_idx: usize,
+        #[serde(default)]
+        path_offset: f32,
         path: Vec<(u16, u16)>,
+        // Shoppers who were sent home before they finished shopping bring back no supplies.
+        #[serde(default)]
+        empty_handed: bool,
     },
     FollowHeadOfHousehold,
 }
 
+#[derive(Clone, Deserialize, Serialize)]
 struct HouseholdState {
     head_of_household_idx: usize,
 
@@ -103,16 +344,93 @@ struct HouseholdState {
     supplies_bought_per_trip: f32,
 
     supply_levels: f32,
+
+    // Isolated households stay home, even when they run out of supplies.
+    isolated: bool,
+
+    // Indices of the people in the household, in increasing order.
+    member_idxs: Vec<usize>,
+
+    // The household is quarantined until this tick, because a member showed symptoms.
+    quarantine_end_tick: usize,
+
+    // The store the household goes to with the home store choice.
+    home_store_idx: usize,
+
+    // The store the household's shopper is heading to, or shopping at.
+    store_idx: usize,
+
+    // Senior households may shop during senior special hours.
+    senior: bool,
+}
+
+impl OpeningHours {
+    fn is_in_window(start: usize, end: usize, time_of_day: usize) -> bool {
+        if start <= end {
+            (start..end).contains(&time_of_day)
+        } else {
+            time_of_day >= start || time_of_day < end
+        }
+    }
+
+    // Whether a household may start a shopping trip at the time of day.
+    fn is_open_for(&self, time_of_day: usize, senior: bool) -> bool {
+        for window in self.special_hours.iter() {
+            if Self::is_in_window(
+                window.start_time_of_day,
+                window.end_time_of_day,
+                time_of_day,
+            ) {
+                return match window.shoppers {
+                    SpecialHoursShoppers::Seniors => senior,
+                };
+            }
+        }
+
+        Self::is_in_window(self.open_time_of_day, self.close_time_of_day, time_of_day)
+    }
+}
+
+// Who may leave a household to go shopping during a tick.
+struct ShoppingPlan {
+    // The person who goes to the store when the household runs out of supplies, if anyone.
+    shopper_idx: Option<usize>,
+
+    // Whether the rest of the household goes along with the shopper.
+    dual_shopper: bool,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct StoreQueue {
+    // Number of shoppers inside the store.
+    occupancy: usize,
+
+    // Indices of the people waiting outside the store, in the order they arrived.
+    waiting_idxs: VecDeque<usize>,
+
+    // Pairs of (store, road) points where the store meets a road. People wait on the road
+    // points, and enter the store at the first store point.
+    entrances: Vec<((u16, u16), (u16, u16))>,
 }
 
+#[derive(Clone, Deserialize, Serialize)]
 pub(crate) struct ShopperBehavior {
+    params: ShopperParams,
+    clock: Option<ClockParams>,
     per_person_states: Vec<ShopperState>,
     per_household_states: Vec<HouseholdState>,
+    store_visits: Vec<usize>,
+    store_queues: Vec<StoreQueue>,
+    store_queue_stats: Vec<wasm_view::StoreQueueStats>,
+    walking_speeds: WalkingSpeeds,
+    distancing: Option<Distancing>,
 }
 
 impl ShopperBehavior {
+    // The rest of the world config provides the clock, distancing and walking speeds.
     pub(crate) fn new(
         params: ShopperParams,
+        config: &WorldConfig,
         people: &[Person],
         map: &maps::Map,
         rng: &mut dyn RngCore,
@@ -122,9 +440,23 @@ impl ShopperBehavior {
             params.fraction_dual_shopper_households,
             rng,
         );
+        let senior_households = Self::draw_senior_households(
+            map.households.len(),
+            params.fraction_senior_households,
+            rng,
+        );
 
+        let num_stores = map.stores.len();
         let mut per_household_states = (0..map.households.len())
             .map(|idx| {
+                // Only draw home stores when there is a choice, to keep single store worlds
+                // reproducible.
+                let home_store_idx = if num_stores > 1 {
+                    rng.gen_range(0, num_stores)
+                } else {
+                    0
+                };
+
                 HouseholdState {
                     head_of_household_idx: 0, // To be filled in.
                     dual_shopper: dual_shopper_households[idx],
@@ -132,6 +464,12 @@ impl ShopperBehavior {
                     supplies_bought_per_trip: params.supplies_bought_per_trip,
                     supply_levels: rng
                         .gen_range(params.init_supply_low_range, params.init_supply_high_range),
+                    isolated: false,
+                    member_idxs: vec![],
+                    quarantine_end_tick: 0,
+                    home_store_idx,
+                    store_idx: home_store_idx,
+                    senior: senior_households[idx],
                 }
             })
             .collect::<Vec<_>>();
@@ -139,10 +477,12 @@ impl ShopperBehavior {
         let per_person_states = people
             .iter()
             .map(|p| {
+                let household_state = &mut per_household_states[p.household_idx];
                 if p.head_of_household {
-                    let household_state = &mut per_household_states[p.household_idx];
                     household_state.head_of_household_idx = p.id;
                 }
+                household_state.member_idxs.push(p.id);
+
                 ShopperState::AtHome {
                     direction_rad: rng.gen_range(0.0, 2.0 * PI),
                 }
@@ -150,11 +490,283 @@ impl ShopperBehavior {
             .collect();
 
         ShopperBehavior {
+            params,
+            clock: config.clock,
             per_person_states,
             per_household_states,
+            store_visits: vec![0; num_stores],
+            store_queues: map
+                .stores
+                .iter()
+                .map(|s| StoreQueue {
+                    occupancy: 0,
+                    waiting_idxs: VecDeque::new(),
+                    entrances: map.find_bb_road_intersection(&s.bounds),
+                })
+                .collect(),
+            store_queue_stats: vec![wasm_view::StoreQueueStats::default(); num_stores],
+            walking_speeds: WalkingSpeeds::new(config.walking_speed.as_ref(), people.len(), rng),
+            distancing: config
+                .distancing
+                .map(|params| Distancing::new(params, config.bounding_box, people.len(), rng)),
+        }
+    }
+
+    // Only draws senior households when there are any, to keep worlds without them reproducible.
+    fn draw_senior_households(
+        num_households: usize,
+        fraction_senior_households: f32,
+        rng: &mut dyn RngCore,
+    ) -> Vec<bool> {
+        if fraction_senior_households > 0.0 {
+            random_bool_vec(num_households, fraction_senior_households, rng)
+        } else {
+            vec![false; num_households]
+        }
+    }
+
+    fn has_room(max_store_occupancy: Option<usize>, occupancy: usize) -> bool {
+        match max_store_occupancy {
+            Some(max_store_occupancy) => occupancy < max_store_occupancy,
+            None => true,
+        }
+    }
+
+    // Lets people in from the store queues, one for each shopper who left, and moves everyone
+    // who is still waiting to their place in the queue.
+    fn admit_queued_shoppers(&mut self, people: &mut [Person], rng: &mut dyn RngCore) {
+        let max_store_occupancy = self.params.max_store_occupancy;
+        for (store_queue, stats) in self
+            .store_queues
+            .iter_mut()
+            .zip(self.store_queue_stats.iter_mut())
+        {
+            while Self::has_room(max_store_occupancy, store_queue.occupancy) {
+                let idx = match store_queue.waiting_idxs.pop_front() {
+                    Some(idx) => idx,
+                    None => break,
+                };
+                if let ShopperState::Queueing { wait_ticks } = self.per_person_states[idx] {
+                    stats.num_waits += 1;
+                    stats.total_wait_ticks += wait_ticks;
+                    stats.max_wait_ticks = stats.max_wait_ticks.max(wait_ticks);
+                }
+
+                let (row, col) = store_queue.entrances[0].0;
+                people[idx].position = Position {
+                    x: col as f32,
+                    y: row as f32,
+                };
+                self.per_person_states[idx] = ShopperState::Shopping {
+                    direction_rad: rng.gen_range(0.0, 2.0 * PI),
+                    shopping_duration_ticks: 0,
+                };
+                store_queue.occupancy += 1;
+            }
+
+            let queue_length = store_queue.waiting_idxs.len();
+            stats.total_queue_length += queue_length;
+            stats.max_queue_length = stats.max_queue_length.max(queue_length);
+
+            // The queue wraps around the entrances if it is longer than the number of them.
+            let num_entrances = store_queue.entrances.len();
+            for (queue_idx, idx) in store_queue.waiting_idxs.iter().enumerate() {
+                let (row, col) = store_queue.entrances[queue_idx % num_entrances].1;
+                people[*idx].position = Position {
+                    x: col as f32,
+                    y: row as f32,
+                };
+                if let ShopperState::Queueing { wait_ticks } = &mut self.per_person_states[*idx] {
+                    *wait_ticks += 1;
+                }
+            }
         }
     }
 
+    // Sends shoppers who became symptomatic while out home, without supplies. Shoppers on their
+    // way to the store turn back along the way they came.
+    fn send_isolating_shoppers_home(
+        &mut self,
+        people: &[Person],
+        map: &maps::Map,
+        rng: &mut dyn RngCore,
+    ) {
+        for (idx, person) in people.iter().enumerate() {
+            if !Self::is_isolating(&self.params, person) {
+                continue;
+            }
+
+            let store_idx = self.per_household_states[person.household_idx].store_idx;
+            let path = match &self.per_person_states[idx] {
+                ShopperState::GoingToStore { path_idx, path, .. } => {
+                    let mut path = path[..=(*path_idx).min(path.len() - 1)].to_vec();
+                    path.reverse();
+                    path
+                }
+                ShopperState::Queueing { .. } => {
+                    self.store_queues[store_idx]
+                        .waiting_idxs
+                        .retain(|waiting_idx| *waiting_idx != idx);
+                    Self::find_path_to_home(
+                        &person.position,
+                        store_idx,
+                        person.household_idx,
+                        map,
+                        rng,
+                    )
+                    .expect("failed to find path")
+                }
+                ShopperState::Shopping { .. } => {
+                    self.store_queues[store_idx].occupancy -= 1;
+                    Self::find_path_to_home(
+                        &person.position,
+                        store_idx,
+                        person.household_idx,
+                        map,
+                        rng,
+                    )
+                    .expect("failed to find path")
+                }
+                _ => continue,
+            };
+            self.per_person_states[idx] = ShopperState::ReturningHome {
+                path_idx: 0,
+                path_offset: 0.0,
+                path,
+                empty_handed: true,
+            };
+        }
+    }
+
+    // Picks the store for the next shopping trip of the household.
+    fn choose_store(
+        store_choice: StoreChoice,
+        home_store_idx: usize,
+        household_idx: usize,
+        map: &maps::Map,
+        rng: &mut dyn RngCore,
+    ) -> Result<usize> {
+        let num_stores = map.stores.len();
+        if num_stores == 1 {
+            return Ok(0);
+        }
+
+        let store_idx = match store_choice {
+            StoreChoice::Nearest => {
+                let mut nearest_store_idx = 0;
+                let mut nearest_path_len = usize::MAX;
+                for store_idx in 0..num_stores {
+                    let path_len = map
+                        .get_household_to_store_path(household_idx, store_idx, rng)?
+                        .len();
+                    if path_len < nearest_path_len {
+                        nearest_store_idx = store_idx;
+                        nearest_path_len = path_len;
+                    }
+                }
+                nearest_store_idx
+            }
+            StoreChoice::Random => rng.gen_range(0, num_stores),
+            StoreChoice::WeightedBySize => {
+                WeightedIndex::new(map.stores.iter().map(|s| s.bounds.size()))?.sample(rng)
+            }
+            StoreChoice::HomeStore => home_store_idx,
+        };
+        Ok(store_idx)
+    }
+
+    fn is_isolating(params: &ShopperParams, person: &Person) -> bool {
+        params.symptomatic_isolation.is_some()
+            && matches!(person.disease_state, DiseaseState::Symptomatic(_))
+    }
+
+    // Starts the quarantine of households with symptomatic members, and decides who shops for
+    // each household.
+    fn get_shopping_plans(&mut self, tick: usize, people: &[Person]) -> Vec<ShoppingPlan> {
+        let isolation = self.params.symptomatic_isolation;
+
+        let mut plans = Vec::with_capacity(self.per_household_states.len());
+        for hs in self.per_household_states.iter_mut() {
+            if let Some(quarantine_ticks) =
+                isolation.and_then(|params| params.household_quarantine_ticks)
+            {
+                for member_idx in hs.member_idxs.iter() {
+                    if let DiseaseState::Symptomatic(start_tick) = people[*member_idx].disease_state
+                    {
+                        hs.quarantine_end_tick =
+                            hs.quarantine_end_tick.max(start_tick + quarantine_ticks);
+                    }
+                }
+            }
+
+            // Nobody starts a trip while the stores are closed to the household.
+            let is_open = match (&self.params.opening_hours, self.clock) {
+                (Some(hours), Some(clock)) => hours.is_open_for(clock.time_of_day(tick), hs.senior),
+                _ => true,
+            };
+            if hs.isolated || !is_open {
+                plans.push(ShoppingPlan {
+                    shopper_idx: None,
+                    dual_shopper: false,
+                });
+                continue;
+            }
+
+            let head_isolating =
+                Self::is_isolating(&self.params, &people[hs.head_of_household_idx]);
+            let quarantined = isolation.is_some() && tick < hs.quarantine_end_tick;
+            if !head_isolating && !quarantined {
+                plans.push(ShoppingPlan {
+                    shopper_idx: Some(hs.head_of_household_idx),
+                    dual_shopper: hs.dual_shopper,
+                });
+                continue;
+            }
+
+            let shopper_idx = match isolation.map(|params| params.shopper) {
+                Some(IsolationShopper::HealthyMember) => hs
+                    .member_idxs
+                    .iter()
+                    .find(|idx| {
+                        !matches!(people[**idx].disease_state, DiseaseState::Symptomatic(_))
+                    })
+                    .copied(),
+                Some(IsolationShopper::Nobody) | None => None,
+            };
+            plans.push(ShoppingPlan {
+                shopper_idx,
+                dual_shopper: false,
+            });
+        }
+
+        plans
+    }
+
+    // Moves the person to their current point on the path, then advances their point along the
+    // path by how far they walk in a tick on the current map element. Each step of the path is
+    // one unit long, and positions between steps are interpolated.
+    fn walk_path(
+        person: &mut Person,
+        path: &[(u16, u16)],
+        path_idx: &mut usize,
+        path_offset: &mut f32,
+        walking_speeds: &WalkingSpeeds,
+        map: &maps::Map,
+    ) {
+        let (row, col) = path[*path_idx];
+        person.position.x = col as f32;
+        person.position.y = row as f32;
+        if let Some((next_row, next_col)) = path.get(*path_idx + 1) {
+            person.position.x += *path_offset * (*next_col as f32 - col as f32);
+            person.position.y += *path_offset * (*next_row as f32 - row as f32);
+        }
+
+        let element = map.get_element(row as usize, col as usize);
+        let distance = *path_offset + walking_speeds.get(person.id, element);
+        *path_idx += distance.floor() as usize;
+        *path_offset = distance.fract();
+    }
+
     fn get_linear_path(source: &Position, dest: &Position) -> Vec<(u16, u16)> {
         let mut result = vec![source.clone()];
 
@@ -189,10 +801,11 @@ impl ShopperBehavior {
     fn find_path_to_store(
         starting: &Position,
         household_idx: usize,
+        store_idx: usize,
         map: &maps::Map,
         rng: &mut dyn RngCore,
     ) -> Result<Vec<(u16, u16)>> {
-        let road_path = map.get_household_to_store_path(household_idx, 0, rng)?;
+        let road_path = map.get_household_to_store_path(household_idx, store_idx, rng)?;
 
         // Add intermediate nodes from the starting position to the starting intersection.
         let mut entire_path = Self::get_linear_path(
@@ -208,11 +821,12 @@ impl ShopperBehavior {
 
     fn find_path_to_home(
         starting: &Position,
+        store_idx: usize,
         household_idx: usize,
         map: &maps::Map,
         rng: &mut dyn RngCore,
     ) -> Result<Vec<(u16, u16)>> {
-        let road_path = map.get_store_to_household_path(0, household_idx, rng)?;
+        let road_path = map.get_store_to_household_path(store_idx, household_idx, rng)?;
 
         // Add intermediate nodes from the starting position to the starting intersection.
         let mut entire_path = Self::get_linear_path(
@@ -230,28 +844,57 @@ impl ShopperBehavior {
 impl PersonBehavior for ShopperBehavior {
     fn update_positions(
         &mut self,
+        tick: usize,
         people: &mut [Person],
         maybe_map: &mut Option<maps::Map>,
         rng: &mut dyn RngCore,
     ) {
-        // Step 0: Update the household supply levels.
+        // Step 0: Update the household supply levels, decide who shops for each household, send
+        // shoppers with symptoms home, let people in from the store queues, and steer shoppers
+        // away from each other.
         let map = maybe_map
             .as_mut()
             .expect("shopper behavior must have a map");
         for household_state in self.per_household_states.iter_mut() {
             household_state.supply_levels -= 1.0;
         }
+        let shopping_plans = self.get_shopping_plans(tick, people);
+        self.send_isolating_shoppers_home(people, map, rng);
+        self.admit_queued_shoppers(people, rng);
+        if let Some(distancing) = &self.distancing {
+            let mut directions_rad = self
+                .per_person_states
+                .iter()
+                .map(|state| match state {
+                    ShopperState::Shopping { direction_rad, .. } => Some(*direction_rad),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            distancing.steer(people, &mut directions_rad);
+            for (state, steered_direction_rad) in
+                self.per_person_states.iter_mut().zip(directions_rad)
+            {
+                if let (ShopperState::Shopping { direction_rad, .. }, Some(steered_direction_rad)) =
+                    (state, steered_direction_rad)
+                {
+                    *direction_rad = steered_direction_rad;
+                }
+            }
+        }
 
         // Step 1: For each shopper, update their position:
         //   Case AtHome:
         //     Check the supply levels of the household.
         //     If they are acceptable, then brownian motion within the household.
-        //     If they are the head of household, calculate a path towards the store and update
-        //       state to GoingToStore.
+        //     If they are the household's shopper (usually the head of household), calculate a
+        //       path towards the store and update state to GoingToStore.
         //     If they are in a 2x-shopper household, then
         //   Case GoingToStore:
         //     If they are at the store, then advance the state to GoingToStore.
+        //     If the store is full, then wait in the queue outside instead.
         //     Otherwise, continue on path towards the store.
+        //   Case Queueing:
+        //     Nothing to do, people are let in from the queue at the start of the tick.
         //   Case Shopping:
         //     Check how many supplies they have.
         //     If not enough, then brownian motion within the store and increase supply level.
@@ -269,93 +912,158 @@ impl PersonBehavior for ShopperBehavior {
             let state = &mut right_people_states[0];
 
             let household = &map.households[person.household_idx];
+            let is_isolating = Self::is_isolating(&self.params, person);
             let household_state = &mut self.per_household_states[person.household_idx];
+            let shopping_plan = &shopping_plans[person.household_idx];
 
             match state {
                 ShopperState::AtHome { direction_rad } => {
-                    if household_state.supply_levels > 0.0 {
-                        // Household supply levels are acceptable, brownian motion within household
-                        person.position.advance(direction_rad, &household.bounds);
+                    if household_state.supply_levels > 0.0 || shopping_plan.shopper_idx.is_none() {
+                        // Household supply levels are acceptable or nobody in the household can
+                        // shop, brownian motion within household
+                        person.position.advance(
+                            direction_rad,
+                            self.walking_speeds.get(idx, MapElement::Household),
+                            &household.bounds,
+                        );
                         continue;
                     }
 
-                    if person.head_of_household {
+                    if shopping_plan.shopper_idx == Some(idx) {
+                        let store_idx = Self::choose_store(
+                            self.params.store_choice,
+                            household_state.home_store_idx,
+                            person.household_idx,
+                            map,
+                            rng,
+                        )
+                        .expect("failed to choose store");
+                        household_state.store_idx = store_idx;
                         let path = Self::find_path_to_store(
                             &person.position,
                             person.household_idx,
+                            store_idx,
                             map,
                             rng,
                         )
                         .expect("failed to find path");
-                        *state = ShopperState::GoingToStore { path_idx: 0, path };
+                        *state = ShopperState::GoingToStore {
+                            path_idx: 0,
+                            path_offset: 0.0,
+                            path,
+                        };
                         continue;
-                    } else if household_state.dual_shopper {
+                    } else if shopping_plan.dual_shopper && !is_isolating {
                         *state = ShopperState::FollowHeadOfHousehold;
                         continue;
                     }
 
-                    // This household only has a single shopper (head of household).
-                    // We are not the head, so do not shop.
-                    person.position.advance(direction_rad, &household.bounds);
+                    // This household only has a single shopper, and it is not us.
+                    // People with symptoms also stay home when they are isolating.
+                    person.position.advance(
+                        direction_rad,
+                        self.walking_speeds.get(idx, MapElement::Household),
+                        &household.bounds,
+                    );
                 }
-                ShopperState::GoingToStore { path_idx, path } => {
+                ShopperState::GoingToStore {
+                    path_idx,
+                    path_offset,
+                    path,
+                } => {
                     if *path_idx < path.len() {
-                        person.position.x = path[*path_idx].1 as f32;
-                        person.position.y = path[*path_idx].0 as f32;
+                        Self::walk_path(
+                            person,
+                            path,
+                            path_idx,
+                            path_offset,
+                            &self.walking_speeds,
+                            map,
+                        );
+                    } else {
+                        person.position.x = path[path.len() - 1].1 as f32;
+                        person.position.y = path[path.len() - 1].0 as f32;
+                        self.store_visits[household_state.store_idx] += 1;
 
-                        if map.get_element(path[*path_idx].0 as usize, path[*path_idx].1 as usize)
-                            == MapElement::Road
+                        // Wait outside if the store is full, or if others are already waiting.
+                        let store_queue = &mut self.store_queues[household_state.store_idx];
+                        if store_queue.waiting_idxs.is_empty()
+                            && Self::has_room(
+                                self.params.max_store_occupancy,
+                                store_queue.occupancy,
+                            )
                         {
-                            *path_idx += 3;
+                            store_queue.occupancy += 1;
+                            *state = ShopperState::Shopping {
+                                direction_rad: rng.gen_range(0.0, 2.0 * PI),
+                                shopping_duration_ticks: 0,
+                            };
                         } else {
-                            *path_idx += 1;
+                            store_queue.waiting_idxs.push_back(idx);
+                            let queue_idx = store_queue.waiting_idxs.len() - 1;
+                            let (row, col) =
+                                store_queue.entrances[queue_idx % store_queue.entrances.len()].1;
+                            person.position.x = col as f32;
+                            person.position.y = row as f32;
+                            *state = ShopperState::Queueing { wait_ticks: 0 };
                         }
-                    } else {
-                        person.position.x = path[path.len() - 1].1 as f32;
-                        person.position.y = path[path.len() - 1].0 as f32;
-                        *state = ShopperState::Shopping {
-                            direction_rad: rng.gen_range(0.0, 2.0 * PI),
-                            shopping_duration_ticks: 0,
-                        };
                     }
                 }
+                ShopperState::Queueing { .. } => {
+                    // Queued people are let in and moved along the queue in admit_queued_shoppers.
+                }
                 ShopperState::Shopping {
                     direction_rad,
                     shopping_duration_ticks,
                 } => {
                     if *shopping_duration_ticks < household_state.shopping_period_ticks {
                         *shopping_duration_ticks += 1;
-                        person
-                            .position
-                            .advance(direction_rad, &map.stores[0].bounds);
+                        person.position.advance(
+                            direction_rad,
+                            self.walking_speeds.get(idx, MapElement::Store),
+                            &map.stores[household_state.store_idx].bounds,
+                        );
                     } else {
+                        self.store_queues[household_state.store_idx].occupancy -= 1;
                         let path = Self::find_path_to_home(
                             &person.position,
+                            household_state.store_idx,
                             person.household_idx,
                             map,
                             rng,
                         )
                         .expect("failed to find path");
-                        *state = ShopperState::ReturningHome { path_idx: 0, path };
+                        *state = ShopperState::ReturningHome {
+                            path_idx: 0,
+                            path_offset: 0.0,
+                            path,
+                            empty_handed: false,
+                        };
                     }
                 }
-                ShopperState::ReturningHome { path_idx, path } => {
+                ShopperState::ReturningHome {
+                    path_idx,
+                    path_offset,
+                    path,
+                    empty_handed,
+                } => {
                     if *path_idx < path.len() {
-                        person.position.x = path[*path_idx].1 as f32;
-                        person.position.y = path[*path_idx].0 as f32;
-
-                        if map.get_element(path[*path_idx].0 as usize, path[*path_idx].1 as usize)
-                            == MapElement::Road
-                        {
-                            *path_idx += 3;
-                        } else {
-                            *path_idx += 1;
-                        }
+                        Self::walk_path(
+                            person,
+                            path,
+                            path_idx,
+                            path_offset,
+                            &self.walking_speeds,
+                            map,
+                        );
                     } else {
                         person.position.x = path[path.len() - 1].1 as f32;
                         person.position.y = path[path.len() - 1].0 as f32;
 
-                        household_state.supply_levels += household_state.supplies_bought_per_trip;
+                        if !*empty_handed {
+                            household_state.supply_levels +=
+                                household_state.supplies_bought_per_trip;
+                        }
 
                         *state = ShopperState::AtHome {
                             direction_rad: rng.gen_range(0.0, 2.0 * PI),
@@ -406,15 +1114,550 @@ impl PersonBehavior for ShopperBehavior {
         state.dual_shopper = hs.dual_shopper;
         state.supply_levels = hs.supply_levels;
     }
+
+    fn checkpoint(&self) -> PersonBehaviorCheckpoint {
+        PersonBehaviorCheckpoint::Shopper(self.clone())
+    }
+
+    fn set_shopper_params(&mut self, params: ShopperParams, rng: &mut dyn RngCore) {
+        // Only redraw the dual shopper households if the fraction changes, so that the other
+        // parameters can be changed without reshuffling households.
+        if params.fraction_dual_shopper_households != self.params.fraction_dual_shopper_households {
+            let dual_shopper_households = random_bool_vec(
+                self.per_household_states.len(),
+                params.fraction_dual_shopper_households,
+                rng,
+            );
+            for (hs, dual_shopper) in self
+                .per_household_states
+                .iter_mut()
+                .zip(dual_shopper_households)
+            {
+                hs.dual_shopper = dual_shopper;
+            }
+        }
+
+        if params.fraction_senior_households != self.params.fraction_senior_households {
+            let senior_households = Self::draw_senior_households(
+                self.per_household_states.len(),
+                params.fraction_senior_households,
+                rng,
+            );
+            for (hs, senior) in self.per_household_states.iter_mut().zip(senior_households) {
+                hs.senior = senior;
+            }
+        }
+
+        for hs in self.per_household_states.iter_mut() {
+            hs.shopping_period_ticks = params.shopping_period_ticks;
+            hs.supplies_bought_per_trip = params.supplies_bought_per_trip;
+        }
+
+        self.params = params;
+    }
+
+    fn set_household_isolation(&mut self, household_idx: usize, isolated: bool) {
+        self.per_household_states[household_idx].isolated = isolated;
+    }
+
+    fn get_store_visits(&self) -> Option<&[usize]> {
+        Some(&self.store_visits)
+    }
+
+    fn get_store_queue_stats(&self) -> Option<&[wasm_view::StoreQueueStats]> {
+        Some(&self.store_queue_stats)
+    }
+
+    fn is_queueing(&self, idx: usize) -> bool {
+        matches!(self.per_person_states[idx], ShopperState::Queueing { .. })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::city::config::DistributionParams;
+    use crate::v0::checkpoint::SeededRng;
+    use crate::v0::config::{
+        BehaviorParameters, DiseaseSpreadParameters, InfectionRadiusParams, InfectiousStateValues,
+        SpecialHours, SymptomParams, SymptomaticIsolationParams, WorldConfig,
+    };
+    use crate::v0::core::World;
     use crate::v0::maps::simple_groceries;
+    use crate::v0::test_util;
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
 
+    // A world where every household is out of supplies from the start, the disease does not
+    // spread, and the infected show symptoms as soon as they become infectious.
+    fn shopping_config(
+        num_initially_infected: usize,
+        symptomatic_isolation: Option<SymptomaticIsolationParams>,
+    ) -> WorldConfig {
+        let mut config = test_util::shopping_config();
+        config.disease_parameters.exposed_period_ticks = DistributionParams::Fixed(0.0);
+        config.disease_parameters.infectious_period_ticks = DistributionParams::Fixed(1000.0);
+        config.disease_parameters.symptoms = Some(SymptomParams {
+            fraction_asymptomatic: 0.0,
+            presymptomatic_period_ticks: DistributionParams::Fixed(0.0),
+        });
+        config.disease_parameters.spread_parameters =
+            DiseaseSpreadParameters::InfectionRadius(InfectionRadiusParams {
+                radius: 0.0,
+                radius_multipliers: InfectiousStateValues::default(),
+            });
+        config.behavior_parameters = BehaviorParameters::Shopper(ShopperParams {
+            init_supply_low_range: -10.0,
+            init_supply_high_range: -5.0,
+            fraction_dual_shopper_households: 1.0,
+            symptomatic_isolation,
+            ..test_util::shopper_params()
+        });
+        config.num_initially_infected = num_initially_infected;
+        config
+    }
+
+    // Returns whether each person left their household at any point in the given number of ticks.
+    fn run_and_find_people_who_left_home(world: &mut World, num_ticks: usize) -> Vec<bool> {
+        let mut left_home = vec![false; world.people.len()];
+        for _ in 0..num_ticks {
+            world.step();
+
+            let map = world.map.as_ref().unwrap();
+            for (p, left_home) in world.people.iter().zip(left_home.iter_mut()) {
+                let bounds = &map.households[p.household_idx].bounds;
+                if !bounds.rows().contains(&(p.position.y as usize))
+                    || !bounds.cols().contains(&(p.position.x as usize))
+                {
+                    *left_home = true;
+                }
+            }
+        }
+        left_home
+    }
+
+    #[test]
+    fn test_symptomatic_people_isolate() -> Result<()> {
+        let mut world = World::new(Box::new(SeededRng::new(10914)), shopping_config(108, None))?;
+        assert!(run_and_find_people_who_left_home(&mut world, 100)
+            .iter()
+            .any(|left_home| *left_home));
+
+        let isolation = SymptomaticIsolationParams {
+            household_quarantine_ticks: None,
+            shopper: IsolationShopper::HealthyMember,
+        };
+        let mut world = World::new(
+            Box::new(SeededRng::new(10914)),
+            shopping_config(108, Some(isolation)),
+        )?;
+        assert!(run_and_find_people_who_left_home(&mut world, 100)
+            .iter()
+            .all(|left_home| !*left_home));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_symptomatic_isolation_requires_symptoms() {
+        let isolation = SymptomaticIsolationParams {
+            household_quarantine_ticks: None,
+            shopper: IsolationShopper::HealthyMember,
+        };
+        let mut config = shopping_config(108, Some(isolation));
+        config.disease_parameters.symptoms = None;
+
+        assert!(World::new(Box::new(SeededRng::new(10914)), config).is_err());
+    }
+
+    // Runs a world where only the head of the first household shows symptoms, for the given
+    // number of ticks. Returns whether the head and the other member of the household left home.
+    fn run_with_symptomatic_head(
+        isolation: SymptomaticIsolationParams,
+        symptomatic_period_ticks: usize,
+    ) -> Result<(bool, bool)> {
+        let mut world = World::new(
+            Box::new(SeededRng::new(10914)),
+            shopping_config(0, Some(isolation)),
+        )?;
+        assert!(world.people[0].head_of_household);
+        assert_eq!(world.people[1].household_idx, 0);
+        world.people[0].disease_state = DiseaseState::Symptomatic(0);
+        world.people[0].disease_period_ticks = Some(symptomatic_period_ticks);
+
+        let left_home = run_and_find_people_who_left_home(&mut world, 100);
+        assert!(left_home[2]);
+        Ok((left_home[0], left_home[1]))
+    }
+
+    #[test]
+    fn test_healthy_member_shops_for_isolating_head() -> Result<()> {
+        let mut isolation = SymptomaticIsolationParams {
+            household_quarantine_ticks: None,
+            shopper: IsolationShopper::Nobody,
+        };
+        assert_eq!(run_with_symptomatic_head(isolation, 1000)?, (false, false));
+
+        isolation.shopper = IsolationShopper::HealthyMember;
+        assert_eq!(run_with_symptomatic_head(isolation, 1000)?, (false, true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_household_quarantine_outlasts_symptoms() -> Result<()> {
+        let mut isolation = SymptomaticIsolationParams {
+            household_quarantine_ticks: None,
+            shopper: IsolationShopper::Nobody,
+        };
+        assert_eq!(run_with_symptomatic_head(isolation, 5)?, (true, true));
+
+        isolation.household_quarantine_ticks = Some(500);
+        assert_eq!(run_with_symptomatic_head(isolation, 5)?, (false, false));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shoppers_who_become_symptomatic_go_home() -> Result<()> {
+        let isolation = SymptomaticIsolationParams {
+            household_quarantine_ticks: None,
+            shopper: IsolationShopper::Nobody,
+        };
+        let get_head_state = |world: &World| match world.person_behavior.checkpoint() {
+            PersonBehaviorCheckpoint::Shopper(behavior) => behavior.per_person_states[0].clone(),
+            _ => unreachable!(),
+        };
+
+        for in_store in [false, true].iter() {
+            let mut world = World::new(
+                Box::new(SeededRng::new(10914)),
+                shopping_config(0, Some(isolation)),
+            )?;
+            assert!(world.people[0].head_of_household);
+
+            // The head of the first household shows symptoms partway to the store, or while
+            // shopping.
+            loop {
+                world.step();
+                let out_shopping = match get_head_state(&world) {
+                    ShopperState::GoingToStore { path_idx, .. } => !*in_store && path_idx > 2,
+                    ShopperState::Shopping { .. } => *in_store,
+                    _ => false,
+                };
+                if out_shopping {
+                    break;
+                }
+            }
+            world.people[0].disease_state = DiseaseState::Symptomatic(world.tick);
+            world.people[0].disease_period_ticks = Some(1000);
+
+            world.step();
+            assert!(matches!(
+                get_head_state(&world),
+                ShopperState::ReturningHome {
+                    empty_handed: true,
+                    ..
+                }
+            ));
+
+            for _ in 0..300 {
+                world.step();
+                assert!(!matches!(
+                    get_head_state(&world),
+                    ShopperState::Shopping { .. } | ShopperState::Queueing { .. }
+                ));
+            }
+            assert!(matches!(
+                get_head_state(&world),
+                ShopperState::AtHome { .. }
+            ));
+            let map = world.map.as_ref().unwrap();
+            let bounds = &map.households[world.people[0].household_idx].bounds;
+            assert!(bounds
+                .rows()
+                .contains(&(world.people[0].position.y as usize)));
+            assert!(bounds
+                .cols()
+                .contains(&(world.people[0].position.x as usize)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_choice() -> Result<()> {
+        // A small store below the left household, and a large store above the right household.
+        let map = maps::Map::load_from_ascii_str("HH..SSS\nRRRRRRR\nS....HH", 1, 1)?;
+        assert_eq!(map.stores.len(), 2);
+        let (small_store_idx, large_store_idx) = if map.stores[0].bounds.size() == 1 {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+        let left_household_idx = if map.households[0].bounds.left == 0 {
+            0
+        } else {
+            1
+        };
+
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let mut choices = |store_choice, household_idx| -> Result<Vec<usize>> {
+            (0..200)
+                .map(|_| {
+                    ShopperBehavior::choose_store(
+                        store_choice,
+                        small_store_idx,
+                        household_idx,
+                        &map,
+                        &mut rng,
+                    )
+                })
+                .collect()
+        };
+        let count =
+            |choices: &[usize], store_idx| choices.iter().filter(|idx| **idx == store_idx).count();
+
+        assert!(choices(StoreChoice::Nearest, left_household_idx)?
+            .iter()
+            .all(|idx| *idx == small_store_idx));
+        assert!(choices(StoreChoice::Nearest, 1 - left_household_idx)?
+            .iter()
+            .all(|idx| *idx == large_store_idx));
+        assert!(choices(StoreChoice::HomeStore, 1 - left_household_idx)?
+            .iter()
+            .all(|idx| *idx == small_store_idx));
+
+        let random = choices(StoreChoice::Random, left_household_idx)?;
+        assert!((80..120).contains(&count(&random, small_store_idx)));
+        let weighted = choices(StoreChoice::WeightedBySize, left_household_idx)?;
+        assert!((30..70).contains(&count(&weighted, small_store_idx)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_capacity() -> Result<()> {
+        let mut config = shopping_config(0, None);
+        if let BehaviorParameters::Shopper(params) = &mut config.behavior_parameters {
+            params.max_store_occupancy = Some(2);
+        }
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+        for _ in 0..200 {
+            world.step();
+
+            let behavior = match world.person_behavior.checkpoint() {
+                PersonBehaviorCheckpoint::Shopper(behavior) => behavior,
+                _ => unreachable!(),
+            };
+            let num_shoppers = behavior
+                .per_person_states
+                .iter()
+                .filter(|state| matches!(state, ShopperState::Shopping { .. }))
+                .count();
+            assert!(num_shoppers <= 2);
+            assert_eq!(behavior.store_queues[0].occupancy, num_shoppers);
+
+            let map = world.map.as_ref().unwrap();
+
+            for p in world.people.iter() {
+                if world.person_behavior.is_queueing(p.id) {
+                    assert_eq!(
+                        map.get_element(p.position.y as usize, p.position.x as usize),
+                        MapElement::Road
+                    );
+                }
+            }
+        }
+
+        let stats = &world.person_behavior.get_store_queue_stats().unwrap()[0];
+        assert!(stats.max_queue_length > 2);
+        assert!(stats.num_waits > 0);
+        assert!(stats.max_wait_ticks >= 50);
+
+        // Without a limit, nobody waits.
+        let mut world = World::new(Box::new(SeededRng::new(10914)), shopping_config(0, None))?;
+        for _ in 0..200 {
+            world.step();
+        }
+        let stats = &world.person_behavior.get_store_queue_stats().unwrap()[0];
+        assert_eq!(*stats, wasm_view::StoreQueueStats::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_households_wait_for_opening_hours() -> Result<()> {
+        let mut config = shopping_config(0, None);
+        config.clock = Some(ClockParams {
+            ticks_per_day: 100,
+            start_time_of_day: 0,
+        });
+        if let BehaviorParameters::Shopper(params) = &mut config.behavior_parameters {
+            params.fraction_senior_households = 0.5;
+            params.opening_hours = Some(OpeningHours {
+                open_time_of_day: 50,
+                close_time_of_day: 80,
+                special_hours: vec![SpecialHours {
+                    start_time_of_day: 40,
+                    end_time_of_day: 50,
+                    shoppers: SpecialHoursShoppers::Seniors,
+                }],
+            });
+        }
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+        let behavior = match world.person_behavior.checkpoint() {
+            PersonBehaviorCheckpoint::Shopper(behavior) => behavior,
+            _ => unreachable!(),
+        };
+
+        let mut ticks_left_home = vec![None; world.people.len()];
+        for tick in 1..100 {
+            let left_home = run_and_find_people_who_left_home(&mut world, 1);
+            for (tick_left_home, left_home) in ticks_left_home.iter_mut().zip(left_home) {
+                if left_home && tick_left_home.is_none() {
+                    *tick_left_home = Some(tick);
+                }
+            }
+        }
+
+        let mut num_senior_shoppers = 0;
+        let mut num_other_shoppers = 0;
+        for (p, tick_left_home) in world.people.iter().zip(ticks_left_home) {
+            let senior = behavior.per_household_states[p.household_idx].senior;
+            match tick_left_home {
+                Some(tick) if tick < 50 => {
+                    assert!(senior && tick > 40);
+                    num_senior_shoppers += 1;
+                }
+                Some(_) if !senior => num_other_shoppers += 1,
+                _ => {}
+            }
+        }
+        assert!(num_senior_shoppers > 0);
+        assert!(num_other_shoppers > 0);
+
+        Ok(())
+    }
+
+    // Number of pairs of people within the distance of each other, summed over the given number of
+    // ticks in a crowded world without a map.
+    fn count_close_pairs(
+        distancing: Option<DistancingParams>,
+        distance: f32,
+        num_ticks: usize,
+    ) -> Result<usize> {
+        let mut config = shopping_config(0, None);
+        config.behavior_parameters = BehaviorParameters::BrownianMotion;
+        config.map_params = None;
+        config.bounding_box = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 40,
+            right: 40,
+        };
+        config.num_people = 200;
+        config.distancing = distancing;
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+
+        let mut num_close_pairs = 0;
+        for _ in 0..num_ticks {
+            world.step();
+            for (idx, p) in world.people.iter().enumerate() {
+                num_close_pairs += world.people[idx + 1..]
+                    .iter()
+                    .filter(|other| p.position.distance(&other.position) < distance)
+                    .count();
+            }
+        }
+        Ok(num_close_pairs)
+    }
+
+    #[test]
+    fn test_distancing_keeps_people_apart() -> Result<()> {
+        let mut distancing = DistancingParams {
+            personal_space_radius: 3.0,
+            repulsion_strength: 2.0,
+            fraction_compliant: 1.0,
+        };
+        let num_undistanced_pairs = count_close_pairs(None, 1.0, 50)?;
+        let num_distanced_pairs = count_close_pairs(Some(distancing), 1.0, 50)?;
+        assert!(num_distanced_pairs * 2 < num_undistanced_pairs);
+
+        // Nobody complying is the same as no distancing at all.
+        distancing.fraction_compliant = 0.0;
+        assert_eq!(
+            count_close_pairs(Some(distancing), 1.0, 50)?,
+            num_undistanced_pairs
+        );
+
+        Ok(())
+    }
+
+    // Runs a shopping world until the first shopper arrives at the store. Returns the tick of the
+    // arrival, and whether any shopper was between two cells of a road on the way.
+    fn run_until_first_store_visit(
+        walking_speed: Option<WalkingSpeedParams>,
+    ) -> Result<(usize, bool)> {
+        let mut config = shopping_config(0, None);
+        config.walking_speed = walking_speed;
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+
+        let mut between_road_cells = false;
+        while world.person_behavior.get_store_visits().unwrap()[0] == 0 {
+            world.step();
+
+            let map = world.map.as_ref().unwrap();
+            between_road_cells |= world.people.iter().any(|p| {
+                p.head_of_household
+                    && map.get_element(p.position.y as usize, p.position.x as usize)
+                        == MapElement::Road
+                    && (p.position.x.fract() != 0.0 || p.position.y.fract() != 0.0)
+            });
+        }
+        Ok((world.tick, between_road_cells))
+    }
+
+    #[test]
+    fn test_walking_speed() -> Result<()> {
+        let (default_tick, default_between_road_cells) = run_until_first_store_visit(None)?;
+        assert!(!default_between_road_cells);
+
+        // Walking at half speed, with no speed-up on roads, takes longer and moves people part of
+        // the way between road cells.
+        let (slow_tick, slow_between_road_cells) =
+            run_until_first_store_visit(Some(WalkingSpeedParams {
+                speed: DistributionParams::Fixed(0.5),
+                element_multipliers: vec![],
+            }))?;
+        assert!(slow_tick > default_tick);
+        assert!(slow_between_road_cells);
+
+        let (fast_tick, _) = run_until_first_store_visit(Some(WalkingSpeedParams {
+            speed: DistributionParams::Uniform(2.0, 3.0),
+            element_multipliers: vec![ElementSpeedMultiplier {
+                element: MapElement::Road,
+                multiplier: 3.0,
+            }],
+        }))?;
+        assert!(fast_tick < default_tick);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_visits() -> Result<()> {
+        let mut world = World::new(Box::new(SeededRng::new(10914)), shopping_config(0, None))?;
+        for _ in 0..100 {
+            world.step();
+        }
+        let visits = world.person_behavior.get_store_visits().unwrap();
+        assert_eq!(visits.len(), 1);
+        assert!(visits[0] > 0);
+
+        Ok(())
+    }
+
     #[test]
     fn test_find_path() -> Result<()> {
         let mut rng = Box::new(ChaCha8Rng::seed_from_u64(10914));
@@ -434,6 +1677,7 @@ mod tests {
         let path = ShopperBehavior::find_path_to_store(
             &Position { x: 65.0, y: 125.0 },
             household_idx,
+            0,
             &sg_map,
             &mut rng,
         )?;
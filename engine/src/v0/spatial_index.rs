@@ -0,0 +1,156 @@
+// Spatial index for finding people near a position without comparing every pair of people.
+
+use crate::v0::geometry::{BoundingBox, Position};
+
+// Uniform grid over the world, where every item is bucketed into the cell that contains its
+// position. Items outside of the world are bucketed into the nearest cell on the boundary.
+pub(crate) struct UniformGrid {
+    left: f32,
+    bottom: f32,
+    cell_size: f32,
+
+    num_rows: usize,
+    num_cols: usize,
+
+    // (cell index, item) pairs, sorted by cell index.
+    // Storing only the occupied cells keeps the memory proportional to the number of items, even
+    // when the world is large relative to the cell size.
+    entries: Vec<(usize, usize)>,
+}
+
+impl UniformGrid {
+    pub(crate) fn new<'a, I>(world_bb: &BoundingBox, cell_size: f32, items: I) -> Self
+    where
+        I: Iterator<Item = (usize, &'a Position)>,
+    {
+        assert!(cell_size > 0.0);
+
+        let width = (world_bb.right - world_bb.left) as f32;
+        let height = (world_bb.top - world_bb.bottom) as f32;
+
+        let mut grid = UniformGrid {
+            left: world_bb.left as f32,
+            bottom: world_bb.bottom as f32,
+            cell_size,
+            num_rows: ((height / cell_size).ceil() as usize).max(1),
+            num_cols: ((width / cell_size).ceil() as usize).max(1),
+            entries: vec![],
+        };
+
+        let mut entries = items
+            .map(|(item, pos)| {
+                let (row, col) = grid.get_cell(pos.x, pos.y);
+                (row * grid.num_cols + col, item)
+            })
+            .collect::<Vec<_>>();
+        // Stable sort, so items within a cell keep their insertion order.
+        entries.sort_by_key(|(cell_idx, _)| *cell_idx);
+        grid.entries = entries;
+
+        grid
+    }
+
+    fn get_cell(&self, x: f32, y: f32) -> (usize, usize) {
+        let row = ((y - self.bottom) / self.cell_size).floor().max(0.0) as usize;
+        let col = ((x - self.left) / self.cell_size).floor().max(0.0) as usize;
+
+        (row.min(self.num_rows - 1), col.min(self.num_cols - 1))
+    }
+
+    // Returns all items in the cells that overlap the square of the given radius around pos.
+    // This is a superset of the items within the radius; callers are expected to check the
+    // distance themselves.
+    pub(crate) fn query<'a>(
+        &'a self,
+        pos: &Position,
+        radius: f32,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let (min_row, min_col) = self.get_cell(pos.x - radius, pos.y - radius);
+        let (max_row, max_col) = self.get_cell(pos.x + radius, pos.y + radius);
+
+        (min_row..=max_row).flat_map(move |row| {
+            // Cells within a row are contiguous, so each row is a single range of entries.
+            let first_cell_idx = row * self.num_cols + min_col;
+            let last_cell_idx = row * self.num_cols + max_col;
+
+            let start = self
+                .entries
+                .partition_point(|(cell_idx, _)| *cell_idx < first_cell_idx);
+            self.entries[start..]
+                .iter()
+                .take_while(move |(cell_idx, _)| *cell_idx <= last_cell_idx)
+                .map(|(_, item)| *item)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_query_finds_all_items_within_radius() {
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 40,
+            right: 60,
+        };
+        let positions = (0..500)
+            .map(|_| world_bb.random_point(&mut rng))
+            .collect::<Vec<_>>();
+        let radius = 3.2;
+
+        let grid = UniformGrid::new(&world_bb, radius, positions.iter().enumerate());
+
+        for _ in 0..100 {
+            let pos = world_bb.random_point(&mut rng);
+
+            let mut expected = positions
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.distance(&pos) < radius)
+                .map(|(idx, _)| idx)
+                .collect::<Vec<_>>();
+            expected.sort();
+
+            let mut actual = grid
+                .query(&pos, radius)
+                .filter(|idx| positions[*idx].distance(&pos) < radius)
+                .collect::<Vec<_>>();
+            actual.sort();
+
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_query_outside_of_world() {
+        let world_bb = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 10,
+            right: 10,
+        };
+        let positions = [
+            Position { x: -1.0, y: 5.0 },
+            Position { x: 5.0, y: 11.0 },
+            Position { x: 5.0, y: 5.0 },
+        ];
+
+        let grid = UniformGrid::new(&world_bb, 2.0, positions.iter().enumerate());
+
+        let near_left = grid
+            .query(&Position { x: 0.5, y: 5.0 }, 2.0)
+            .collect::<Vec<_>>();
+        assert_eq!(near_left, vec![0]);
+
+        let near_top = grid
+            .query(&Position { x: 5.0, y: 10.5 }, 1.0)
+            .collect::<Vec<_>>();
+        assert_eq!(near_top, vec![1]);
+    }
+}
@@ -0,0 +1,97 @@
+// World configs shared by the tests. Tests start from one of these and override the fields that
+// they care about.
+
+use crate::city::config::DistributionParams;
+use crate::v0::config::{
+    BackgroundViralParticleParams, BehaviorParameters, DiseaseParameters, DiseaseSpreadParameters,
+    ExhaleKernel, InfectionModel, InfectionRadiusParams, InfectiousStateValues, MapParams,
+    MaskFiltrationTable, MiscParams, ShopperParams, StoreChoice, WorldConfig,
+};
+use crate::v0::geometry::BoundingBox;
+
+// Two people per household shopping on the simple groceries map, with airborne spread.
+pub(crate) fn shopping_config() -> WorldConfig {
+    WorldConfig {
+        disease_parameters: DiseaseParameters {
+            exposed_period_ticks: DistributionParams::Fixed(50.0),
+            infectious_period_ticks: DistributionParams::Fixed(200.0),
+            waning_immunity: None,
+            symptoms: None,
+            spread_parameters: DiseaseSpreadParameters::BackgroundViralParticle(
+                BackgroundViralParticleParams {
+                    exhale_radius: 9.0,
+                    exhale_kernel: ExhaleKernel::Uniform,
+                    decay_rate: 0.055,
+                    ventilation: vec![],
+                    walls: false,
+                    air_movement: None,
+                    infection_risk_per_particle: 0.004,
+                    infection_model: InfectionModel::PerTick,
+                    exhale_multipliers: InfectiousStateValues::default(),
+                },
+            ),
+        },
+        behavior_parameters: BehaviorParameters::Shopper(shopper_params()),
+        bounding_box: BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 400,
+            right: 600,
+        },
+        num_people: 108,
+        num_initially_infected: 10,
+        map_params: Some(MapParams {
+            name: "simple_groceries".to_string(),
+            scale: 10,
+            num_people_per_household: 2,
+        }),
+        misc_parameters: no_masks(),
+        interventions: vec![],
+        policies: vec![],
+        vaccination: None,
+        clock: None,
+        distancing: None,
+        walking_speed: None,
+    }
+}
+
+// People moving randomly without a map, infecting everyone within the given radius.
+pub(crate) fn brownian_motion_config(radius: f32) -> WorldConfig {
+    let mut config = shopping_config();
+    config.disease_parameters.spread_parameters =
+        DiseaseSpreadParameters::InfectionRadius(InfectionRadiusParams {
+            radius,
+            radius_multipliers: InfectiousStateValues::default(),
+        });
+    config.behavior_parameters = BehaviorParameters::BrownianMotion;
+    config.num_people = 100;
+    config.map_params = None;
+    config
+}
+
+pub(crate) fn shopper_params() -> ShopperParams {
+    ShopperParams {
+        shopping_period_ticks: 50,
+        init_supply_low_range: 10.0,
+        init_supply_high_range: 100.0,
+        supplies_bought_per_trip: 300.0,
+        fraction_dual_shopper_households: 0.5,
+        symptomatic_isolation: None,
+        store_choice: StoreChoice::Nearest,
+        max_store_occupancy: None,
+        opening_hours: None,
+        fraction_senior_households: 0.0,
+    }
+}
+
+pub(crate) fn no_masks() -> MiscParams {
+    MiscParams {
+        fraction_mask: 0.0,
+        fraction_n95_mask: 0.0,
+        fraction_cloth_mask: 0.0,
+        fraction_surgical_mask: 0.0,
+        fraction_respirator_mask: 0.0,
+        mask_fit: None,
+        mask_filtration: MaskFiltrationTable::default(),
+    }
+}
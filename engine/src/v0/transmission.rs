@@ -0,0 +1,330 @@
+// Records of who infected whom, and where.
+//
+// Every exposure in a world is recorded as a TransmissionEvent. The events can be used to rebuild
+// the transmission tree, which gives direct measurements of secondary cases and generation times.
+
+use crate::v0::maps::MapElement;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum TransmissionSource {
+    // The person was infected at the start of the simulation.
+    #[serde(rename = "initially_infected")]
+    InitiallyInfected,
+
+    // The person was infected by being close to an infectious person.
+    #[serde(rename = "close_contact")]
+    CloseContact { infector: usize },
+
+    // The person was infected by inhaling background viral particles.
+    // The cell is the (x, y) coordinate of the particle grid the person inhaled from.
+    #[serde(rename = "viral_particles")]
+    ViralParticles { dose: f32, cell: (usize, usize) },
+
+    // The person was infected by a member of their household.
+    #[serde(rename = "household")]
+    Household { infector: usize },
+
+    // The person was infected by touching a contaminated surface.
+    // The cell is the (x, y) coordinate of the contaminated cell, and the dose is the
+    // contamination picked up.
+    #[serde(rename = "fomite")]
+    Fomite { dose: f32, cell: (usize, usize) },
+}
+
+impl TransmissionSource {
+    pub fn infector(&self) -> Option<usize> {
+        match self {
+            TransmissionSource::CloseContact { infector }
+            | TransmissionSource::Household { infector } => Some(*infector),
+            TransmissionSource::InitiallyInfected
+            | TransmissionSource::ViralParticles { .. }
+            | TransmissionSource::Fomite { .. } => None,
+        }
+    }
+}
+
+// An exposure reported by a disease spreader.
+pub(crate) struct Exposure {
+    pub(crate) infectee: usize,
+    pub(crate) source: TransmissionSource,
+    // Index of the route in a composite spread model that caused the exposure.
+    pub(crate) route: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct TransmissionEvent {
+    pub tick: usize,
+    pub infectee: usize,
+    pub source: TransmissionSource,
+
+    // The map element the infectee was on when they were exposed, if the world has a map.
+    pub location: Option<MapElement>,
+
+    // With a composite spread model, the index of the route that caused the exposure.
+    #[serde(default)]
+    pub route: Option<usize>,
+}
+
+// One infection of one person. A person who is reinfected has a node for each of their
+// infections.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TransmissionNode {
+    pub person: usize,
+    pub infected_tick: usize,
+    pub infector: Option<usize>,
+
+    // Index of the node for the infector's infection that caused this one.
+    pub parent: Option<usize>,
+    // Indices of the nodes for the infections that this one caused.
+    pub children: Vec<usize>,
+}
+
+// The nodes are in the same order as the events they were built from.
+#[derive(Serialize, Debug)]
+pub struct TransmissionTree {
+    nodes: Vec<TransmissionNode>,
+}
+
+impl TransmissionTree {
+    // The events must be in the order they happened. Each infection is linked to the infector's
+    // most recent infection before it.
+    pub fn from_events(events: &[TransmissionEvent]) -> Self {
+        let mut nodes: Vec<TransmissionNode> = Vec::with_capacity(events.len());
+        let mut latest_infections: HashMap<usize, usize> = HashMap::new();
+        for (idx, event) in events.iter().enumerate() {
+            let infector = event.source.infector();
+            let parent = infector.and_then(|infector| latest_infections.get(&infector).copied());
+            if let Some(parent) = parent {
+                nodes[parent].children.push(idx);
+            }
+
+            nodes.push(TransmissionNode {
+                person: event.infectee,
+                infected_tick: event.tick,
+                infector,
+                parent,
+                children: vec![],
+            });
+            latest_infections.insert(event.infectee, idx);
+        }
+
+        TransmissionTree { nodes }
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&TransmissionNode> {
+        self.nodes.get(idx)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &TransmissionNode> {
+        self.nodes.iter()
+    }
+
+    // Every infection of the person, in the order they happened.
+    pub fn infections(&self, person: usize) -> impl Iterator<Item = &TransmissionNode> {
+        self.nodes.iter().filter(move |node| node.person == person)
+    }
+
+    // Infections without a known infector: the initial infections, and infections by a route that
+    // does not identify an infector.
+    pub fn roots(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|idx| self.nodes[*idx].parent.is_none())
+            .collect()
+    }
+
+    // Number of infections caused by the person, across all of their own infections.
+    pub fn num_secondary_cases(&self, person: usize) -> usize {
+        self.infections(person)
+            .map(|node| node.children.len())
+            .sum()
+    }
+
+    // Number of ticks between each infection and the infection of the infector that caused it.
+    pub fn generation_intervals(&self) -> Vec<usize> {
+        self.nodes
+            .iter()
+            .filter_map(|node| {
+                let parent = &self.nodes[node.parent?];
+                Some(node.infected_tick - parent.infected_tick)
+            })
+            .collect()
+    }
+}
+
+// Fraction of the household contacts of each household's primary cases that were then infected
+// by a member of their household. The primary cases are the members who were infected first, and
+// households[person] is the household of each person.
+// Infections by other people, or by routes that do not identify an infector, do not count as
+// household transmission. Only each person's first infection counts. Returns None if no household
+// had any contacts.
+pub fn household_secondary_attack_rate(
+    events: &[TransmissionEvent],
+    households: &[usize],
+) -> Option<f32> {
+    let mut first_infections = vec![None; households.len()];
+    for event in events.iter() {
+        let first_infection = &mut first_infections[event.infectee];
+        if first_infection.is_none() {
+            *first_infection = Some(event);
+        }
+    }
+
+    let mut primary_case_ticks = BTreeMap::new();
+    for (person, event) in first_infections.iter().enumerate() {
+        if let Some(event) = event {
+            let primary_tick = primary_case_ticks
+                .entry(households[person])
+                .or_insert(event.tick);
+            *primary_tick = (*primary_tick).min(event.tick);
+        }
+    }
+
+    let mut num_contacts = 0;
+    let mut num_secondary_cases = 0;
+    for (person, event) in first_infections.iter().enumerate() {
+        let household = households[person];
+        let primary_tick = match primary_case_ticks.get(&household) {
+            Some(primary_tick) => *primary_tick,
+            None => continue,
+        };
+        match event {
+            Some(event) if event.tick == primary_tick => {}
+            Some(event) => {
+                num_contacts += 1;
+                if let Some(infector) = event.source.infector() {
+                    if households[infector] == household {
+                        num_secondary_cases += 1;
+                    }
+                }
+            }
+            None => num_contacts += 1,
+        }
+    }
+
+    if num_contacts == 0 {
+        None
+    } else {
+        Some(num_secondary_cases as f32 / num_contacts as f32)
+    }
+}
+
+// Number of exposures that happened on each type of map element.
+pub fn count_exposures_by_location(events: &[TransmissionEvent]) -> HashMap<MapElement, usize> {
+    let mut counts = HashMap::new();
+    for event in events.iter() {
+        if let Some(location) = event.location {
+            *counts.entry(location).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(tick: usize, infectee: usize, source: TransmissionSource) -> TransmissionEvent {
+        TransmissionEvent {
+            tick,
+            infectee,
+            source,
+            location: Some(MapElement::Store),
+            route: None,
+        }
+    }
+
+    #[test]
+    fn test_transmission_tree() {
+        let events = vec![
+            event(0, 0, TransmissionSource::InitiallyInfected),
+            event(5, 3, TransmissionSource::CloseContact { infector: 0 }),
+            event(7, 1, TransmissionSource::CloseContact { infector: 0 }),
+            event(12, 2, TransmissionSource::CloseContact { infector: 3 }),
+            event(
+                13,
+                4,
+                TransmissionSource::ViralParticles {
+                    dose: 0.5,
+                    cell: (10, 20),
+                },
+            ),
+        ];
+        let tree = TransmissionTree::from_events(&events);
+
+        assert_eq!(tree.roots(), vec![0, 4]);
+        assert_eq!(tree.get(0).unwrap().children, vec![1, 2]);
+        assert_eq!(tree.get(1).unwrap().infector, Some(0));
+        assert_eq!(tree.get(1).unwrap().parent, Some(0));
+        assert_eq!(tree.num_secondary_cases(0), 2);
+        assert_eq!(tree.num_secondary_cases(3), 1);
+        assert_eq!(tree.num_secondary_cases(2), 0);
+        assert_eq!(tree.num_secondary_cases(5), 0);
+
+        let mut intervals = tree.generation_intervals();
+        intervals.sort();
+        assert_eq!(intervals, vec![5, 7, 7]);
+
+        assert_eq!(count_exposures_by_location(&events)[&MapElement::Store], 5);
+    }
+
+    #[test]
+    fn test_transmission_tree_with_reinfections() {
+        // Person 0 infects person 1, recovers, loses their immunity, is reinfected by person 1
+        // and then infects person 2.
+        let events = vec![
+            event(0, 0, TransmissionSource::InitiallyInfected),
+            event(5, 1, TransmissionSource::CloseContact { infector: 0 }),
+            event(30, 0, TransmissionSource::CloseContact { infector: 1 }),
+            event(35, 2, TransmissionSource::CloseContact { infector: 0 }),
+        ];
+        let tree = TransmissionTree::from_events(&events);
+
+        assert_eq!(tree.roots(), vec![0]);
+        assert_eq!(
+            tree.infections(0)
+                .map(|node| node.infected_tick)
+                .collect::<Vec<_>>(),
+            vec![0, 30]
+        );
+        assert_eq!(tree.get(0).unwrap().children, vec![1]);
+        assert_eq!(tree.get(2).unwrap().children, vec![3]);
+        assert_eq!(tree.get(3).unwrap().parent, Some(2));
+        assert_eq!(tree.num_secondary_cases(0), 2);
+        assert_eq!(tree.generation_intervals(), vec![5, 25, 5]);
+    }
+
+    #[test]
+    fn test_household_secondary_attack_rate() {
+        // Household 0 has people 0, 1 and 2, household 1 has people 3 and 4, and household 2 has
+        // person 5 on their own.
+        let households = vec![0, 0, 0, 1, 1, 2];
+        assert_eq!(household_secondary_attack_rate(&[], &households), None);
+
+        let events = vec![
+            event(0, 0, TransmissionSource::InitiallyInfected),
+            event(0, 5, TransmissionSource::InitiallyInfected),
+            event(4, 1, TransmissionSource::Household { infector: 0 }),
+            event(6, 3, TransmissionSource::CloseContact { infector: 1 }),
+            // Infections from outside the household do not count.
+            event(8, 2, TransmissionSource::CloseContact { infector: 5 }),
+            event(
+                9,
+                4,
+                TransmissionSource::ViralParticles {
+                    dose: 0.5,
+                    cell: (10, 20),
+                },
+            ),
+            // A reinfection of a primary case does not count.
+            event(20, 0, TransmissionSource::Household { infector: 1 }),
+        ];
+
+        // Person 1 out of the contacts 1, 2 and 4 was infected by their household.
+        assert_eq!(
+            household_secondary_attack_rate(&events, &households),
+            Some(1.0 / 3.0)
+        );
+    }
+}
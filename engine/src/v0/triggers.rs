@@ -0,0 +1,208 @@
+// Policies that are switched on and off by population-level metrics, such as the fraction of
+// people who are infectious.
+
+use crate::v0::config::{PolicyMetric, TriggeredPolicy, WorldConfig};
+use crate::v0::core::{DiseaseState, World};
+use crate::v0::interventions::validate_changes;
+use crate::v0::maps::Map;
+use crate::v0::transmission::TransmissionSource;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct PolicyActivation {
+    pub tick: usize,
+    // Index into the config's policies.
+    pub policy_idx: usize,
+    pub active: bool,
+    // Value of the policy's metric that caused the change.
+    pub metric_value: f32,
+}
+
+pub(crate) fn validate_policies(config: &WorldConfig, map: Option<&Map>) -> Result<()> {
+    for policy in config.policies.iter() {
+        if policy.deactivation_threshold > policy.activation_threshold {
+            return Err(anyhow!(
+                "deactivation threshold must not be above the activation threshold: {:?}",
+                policy
+            ));
+        }
+
+        validate_changes(
+            config,
+            map,
+            policy
+                .on_activation
+                .iter()
+                .chain(policy.on_deactivation.iter()),
+        )?;
+    }
+
+    Ok(())
+}
+
+// Returns whether the policy should be active, given whether it is currently active.
+fn next_policy_state(policy: &TriggeredPolicy, active: bool, metric_value: f32) -> bool {
+    if active {
+        metric_value >= policy.deactivation_threshold
+    } else {
+        metric_value >= policy.activation_threshold
+    }
+}
+
+impl World {
+    fn get_metric_value(&self, metric: PolicyMetric, as_fraction: bool) -> f32 {
+        let count = match metric {
+            PolicyMetric::Susceptible => self
+                .people
+                .iter()
+                .filter(|p| p.disease_state == DiseaseState::Susceptible)
+                .count(),
+            PolicyMetric::Exposed => self
+                .people
+                .iter()
+                .filter(|p| matches!(p.disease_state, DiseaseState::Exposed(_)))
+                .count(),
+            PolicyMetric::Infectious => self
+                .people
+                .iter()
+                .filter(|p| p.disease_state.is_infectious())
+                .count(),
+            PolicyMetric::Symptomatic => self
+                .people
+                .iter()
+                .filter(|p| matches!(p.disease_state, DiseaseState::Symptomatic(_)))
+                .count(),
+            PolicyMetric::Recovered => self
+                .people
+                .iter()
+                .filter(|p| matches!(p.disease_state, DiseaseState::Recovered(_)))
+                .count(),
+            PolicyMetric::NewExposures { window_ticks } => {
+                // Events are recorded in tick order, so only the most recent ones are scanned.
+                self.transmission_events
+                    .iter()
+                    .rev()
+                    .take_while(|event| event.tick + window_ticks > self.tick)
+                    .filter(|event| event.source != TransmissionSource::InitiallyInfected)
+                    .count()
+            }
+        };
+
+        if as_fraction {
+            count as f32 / self.people.len() as f32
+        } else {
+            count as f32
+        }
+    }
+
+    pub(crate) fn update_triggered_policies(&mut self) {
+        for policy_idx in 0..self.config.policies.len() {
+            let policy = &self.config.policies[policy_idx];
+            let metric_value = self.get_metric_value(policy.metric, policy.as_fraction);
+
+            let active = self.active_policies[policy_idx];
+            let next_active = next_policy_state(policy, active, metric_value);
+            if active == next_active {
+                continue;
+            }
+
+            let changes = if next_active {
+                policy.on_activation.clone()
+            } else {
+                policy.on_deactivation.clone()
+            };
+            for change in changes.into_iter() {
+                self.apply_intervention(change);
+            }
+
+            self.active_policies[policy_idx] = next_active;
+            self.policy_activations.push(PolicyActivation {
+                tick: self.tick,
+                policy_idx,
+                active: next_active,
+                metric_value,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::city::config::DistributionParams;
+    use crate::v0::checkpoint::SeededRng;
+    use crate::v0::config::{InterventionChange, MaskFractions};
+    use crate::v0::test_util;
+    use crate::v0::types::Mask;
+
+    fn mask_policy(activation_threshold: f32, deactivation_threshold: f32) -> TriggeredPolicy {
+        TriggeredPolicy {
+            metric: PolicyMetric::Infectious,
+            as_fraction: true,
+            activation_threshold,
+            deactivation_threshold,
+            on_activation: vec![InterventionChange::SetMaskFractions(MaskFractions {
+                fraction_n95_mask: 1.0,
+                ..MaskFractions::default()
+            })],
+            on_deactivation: vec![InterventionChange::SetMaskFractions(
+                MaskFractions::default(),
+            )],
+        }
+    }
+
+    #[test]
+    fn test_next_policy_state_hysteresis() {
+        let policy = mask_policy(0.05, 0.01);
+
+        assert!(!next_policy_state(&policy, false, 0.04));
+        assert!(next_policy_state(&policy, false, 0.05));
+        assert!(next_policy_state(&policy, true, 0.04));
+        assert!(next_policy_state(&policy, true, 0.01));
+        assert!(!next_policy_state(&policy, true, 0.009));
+        assert!(!next_policy_state(&policy, false, 0.02));
+    }
+
+    #[test]
+    fn test_policy_activates_and_deactivates() -> Result<()> {
+        let mut config = test_util::brownian_motion_config(0.1);
+        config.disease_parameters.exposed_period_ticks = DistributionParams::Fixed(0.0);
+        config.disease_parameters.infectious_period_ticks = DistributionParams::Fixed(20.0);
+        config.policies.push(mask_policy(0.05, 0.01));
+        let mut world = World::new(Box::new(SeededRng::new(10914)), config)?;
+
+        world.step();
+        assert_eq!(
+            world.policy_activations,
+            vec![PolicyActivation {
+                tick: 1,
+                policy_idx: 0,
+                active: true,
+                metric_value: 0.1,
+            }]
+        );
+        assert!(world.people.iter().all(|p| matches!(p.mask, Mask::N95)));
+
+        // Everyone recovers once the infectious period ends, which lifts the policy.
+        for _ in 0..30 {
+            world.step();
+        }
+        assert_eq!(world.policy_activations.len(), 2);
+        assert!(!world.policy_activations[1].active);
+        assert!(world.people.iter().all(|p| matches!(p.mask, Mask::None)));
+        assert_eq!(world.intervention_records.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_thresholds() {
+        let mut config = test_util::brownian_motion_config(0.1);
+        config.disease_parameters.exposed_period_ticks = DistributionParams::Fixed(0.0);
+        config.disease_parameters.infectious_period_ticks = DistributionParams::Fixed(20.0);
+        config.policies.push(mask_policy(0.01, 0.05));
+
+        assert!(World::new(Box::new(SeededRng::new(10914)), config).is_err());
+    }
+}
@@ -1,6 +1,6 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Serialize, Debug)]
+#[derive(Copy, Clone, Deserialize, Serialize, Debug, PartialEq)]
 pub enum Mask {
     #[serde(rename = "none")]
     None,
@@ -8,4 +8,10 @@ pub enum Mask {
     Regular,
     #[serde(rename = "n95")]
     N95,
+    #[serde(rename = "cloth")]
+    Cloth,
+    #[serde(rename = "surgical")]
+    Surgical,
+    #[serde(rename = "respirator")]
+    Respirator,
 }
@@ -1,41 +1,83 @@
+use crate::city::config::DistributionParams;
+use crate::v0::config::{MaskFractions, MiscParams};
 use rand::prelude::SliceRandom;
 use rand::RngCore;
 
+// Returns n values in a random order, with each of vals making up its percentage of them, and the
+// rest being val_default.
 pub(crate) fn random_vec<T>(
     n: usize,
-    val_a: T,
-    pct_a: f32,
-    val_b: T,
-    pct_b: f32,
+    vals: &[(T, f32)],
     val_default: T,
     rng: &mut dyn RngCore,
 ) -> Vec<T>
 where
     T: Copy,
 {
-    let num_a = ((n as f32) * pct_a).round() as usize;
-    let num_b = ((n as f32) * pct_b).round() as usize;
-
-    let mut result = (0..n)
-        .map(|idx| {
-            if idx < num_a {
-                val_a
-            } else if idx < num_a + num_b {
-                val_b
-            } else {
-                val_default
-            }
-        })
-        .collect::<Vec<_>>();
+    let mut result = Vec::with_capacity(n);
+    for (val, pct) in vals.iter() {
+        let num = ((n as f32) * pct).round() as usize;
+        let num = num.min(n - result.len());
+        result.resize(result.len() + num, *val);
+    }
+    result.resize(n, val_default);
 
     result.shuffle(rng);
     result
 }
 
 pub(crate) fn random_bool_vec(n: usize, pct_true: f32, rng: &mut dyn RngCore) -> Vec<bool> {
-    let val_b = false;
-    let pct_b = 0.0;
-    random_vec(n, true, pct_true, val_b, pct_b, false, rng)
+    random_vec(n, &[(true, pct_true)], false, rng)
+}
+
+impl DistributionParams {
+    pub(crate) fn sample_ticks(&self, rng: &mut dyn RngCore) -> usize {
+        self.sample(rng).round().max(0.0) as usize
+    }
+
+    // Every sample must be positive, e.g. so that nobody walks at a speed that gets them stuck.
+    pub(crate) fn is_positive(&self) -> bool {
+        match self {
+            DistributionParams::Fixed(x) => *x > 0.0,
+            DistributionParams::Uniform(low, _) => *low > 0.0,
+            DistributionParams::Normal(..) => false,
+            DistributionParams::LogNormal(..) => true,
+        }
+    }
+}
+
+impl MaskFractions {
+    pub(crate) fn is_valid(&self) -> bool {
+        let fractions = [
+            self.fraction_mask,
+            self.fraction_n95_mask,
+            self.fraction_cloth_mask,
+            self.fraction_surgical_mask,
+            self.fraction_respirator_mask,
+        ];
+        fractions
+            .iter()
+            .all(|fraction| (0.0..=1.0).contains(fraction))
+            && fractions.iter().sum::<f32>() <= 1.0
+    }
+}
+
+impl MiscParams {
+    pub(crate) fn set_mask_fractions(&mut self, fractions: &MaskFractions) {
+        self.fraction_mask = fractions.fraction_mask;
+        self.fraction_n95_mask = fractions.fraction_n95_mask;
+        self.fraction_cloth_mask = fractions.fraction_cloth_mask;
+        self.fraction_surgical_mask = fractions.fraction_surgical_mask;
+        self.fraction_respirator_mask = fractions.fraction_respirator_mask;
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        let fit_is_valid = match &self.mask_fit {
+            Some(fit) => fit.is_valid(),
+            None => true,
+        };
+        fit_is_valid && self.mask_filtration.is_valid()
+    }
 }
 
 #[cfg(test)]
@@ -73,6 +115,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sample_ticks() {
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+
+        assert_eq!(DistributionParams::Fixed(7.0).sample_ticks(&mut rng), 7);
+        assert_eq!(DistributionParams::Fixed(-2.0).sample_ticks(&mut rng), 0);
+
+        let uniform = DistributionParams::Uniform(3.0, 5.0);
+        let samples = (0..100)
+            .map(|_| uniform.sample_ticks(&mut rng))
+            .collect::<Vec<_>>();
+        assert!(samples.iter().all(|x| (3..=5).contains(x)));
+        assert!(samples.contains(&3) && samples.contains(&5));
+
+        assert!(!DistributionParams::Uniform(5.0, 3.0).is_valid());
+        assert!(!DistributionParams::LogNormal(1.0, -1.0).is_valid());
+        assert!(!DistributionParams::Uniform(0.0, 1.0).is_positive());
+    }
+
     quickcheck! {
         fn random_bool_vec_num_true(n: usize, pct_true: f32, rnd_seed: u64) -> TestResult {
             if pct_true < 0.0 || pct_true > 1.0 {
@@ -0,0 +1,155 @@
+// Vaccination campaigns for the v0 engine.
+
+use crate::v0::config::{VaccinationAttribute, VaccinationConfig, WorldConfig};
+use crate::v0::core::{Person, World};
+use crate::v0::types::Mask;
+use crate::vaccination::{VaccinationCampaign, VaccinationCandidate, VaccinationPriority};
+use anyhow::{anyhow, Result};
+use rand::RngCore;
+
+pub(crate) fn validate_vaccination(config: &WorldConfig) -> Result<()> {
+    if let Some(params) = &config.vaccination {
+        if params.ticks_per_day == 0 || !params.campaign.efficacy.is_valid() {
+            return Err(anyhow!("invalid vaccination parameters: {:?}", params));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn new_vaccination_campaign(
+    params: &VaccinationConfig,
+    people: &[Person],
+    rng: &mut dyn RngCore,
+) -> VaccinationCampaign {
+    let candidates = people
+        .iter()
+        .map(|p| VaccinationCandidate {
+            household: p.household_idx,
+            has_attribute: match params.campaign.priority {
+                VaccinationPriority::ByAttribute(VaccinationAttribute::HeadOfHousehold) => {
+                    p.head_of_household
+                }
+                VaccinationPriority::ByAttribute(VaccinationAttribute::NoMask) => {
+                    matches!(p.mask, Mask::None)
+                }
+                _ => false,
+            },
+        })
+        .collect::<Vec<_>>();
+
+    VaccinationCampaign::new(&params.campaign, params.ticks_per_day, &candidates, rng)
+}
+
+impl World {
+    // Gives out the doses scheduled for this tick, and updates the protection of everyone who has
+    // been vaccinated.
+    pub(crate) fn vaccinate(&mut self) {
+        let campaign = if let Some(campaign) = &mut self.vaccination_campaign {
+            campaign
+        } else {
+            return;
+        };
+
+        let tick = self.tick;
+        for &idx in campaign.next_doses(tick) {
+            self.people[idx].vaccinated_tick = Some(tick);
+        }
+
+        let efficacy = campaign.efficacy;
+        for p in self.people.iter_mut() {
+            if p.vaccinated_tick.is_none() {
+                continue;
+            }
+            p.vaccine_susceptibility = efficacy.susceptibility_multiplier(p.vaccinated_tick, tick);
+            p.vaccine_infectiousness = efficacy.infectiousness_multiplier(p.vaccinated_tick, tick);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::city::config::DistributionParams;
+    use crate::v0::checkpoint::SeededRng;
+    use crate::v0::core::DiseaseState;
+    use crate::v0::geometry::BoundingBox;
+    use crate::v0::test_util;
+    use crate::v0::transmission::TransmissionSource;
+    use crate::vaccination::{VaccinationParams, VaccineEfficacy};
+
+    fn vaccination_config(susceptibility_reduction: f32) -> WorldConfig {
+        let mut config = test_util::brownian_motion_config(20.0);
+        config.disease_parameters.exposed_period_ticks = DistributionParams::Fixed(0.0);
+        config.disease_parameters.infectious_period_ticks = DistributionParams::Fixed(100.0);
+        config.bounding_box = BoundingBox {
+            bottom: 0,
+            left: 0,
+            top: 100,
+            right: 100,
+        };
+        config.vaccination = Some(VaccinationConfig {
+            ticks_per_day: 10,
+            campaign: VaccinationParams {
+                start_time: 1,
+                doses_per_day: 40,
+                priority: VaccinationPriority::Random,
+                efficacy: VaccineEfficacy {
+                    susceptibility_reduction,
+                    infectiousness_reduction: 0.0,
+                    ramp_up_time: 0,
+                },
+            },
+        });
+        config
+    }
+
+    #[test]
+    fn test_doses_are_given_daily() -> Result<()> {
+        let mut world = World::new(Box::new(SeededRng::new(10914)), vaccination_config(0.0))?;
+        let num_vaccinated = |world: &World| {
+            world
+                .people
+                .iter()
+                .filter(|p| p.vaccinated_tick.is_some())
+                .count()
+        };
+
+        world.step();
+        assert_eq!(num_vaccinated(&world), 40);
+        for _ in 0..10 {
+            world.step();
+        }
+        assert_eq!(num_vaccinated(&world), 80);
+        for _ in 0..10 {
+            world.step();
+        }
+        assert_eq!(num_vaccinated(&world), 100);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vaccinated_people_are_protected() -> Result<()> {
+        let mut world = World::new(Box::new(SeededRng::new(10914)), vaccination_config(1.0))?;
+
+        for _ in 0..50 {
+            world.step();
+        }
+
+        // Nobody who was vaccinated before being exposed is infected.
+        for event in world.transmission_events.iter() {
+            if event.source == TransmissionSource::InitiallyInfected {
+                continue;
+            }
+            let vaccinated_tick = world.people[event.infectee].vaccinated_tick;
+            assert!(!matches!(vaccinated_tick, Some(t) if t <= event.tick));
+        }
+        assert!(world
+            .people
+            .iter()
+            .any(|p| p.disease_state == DiseaseState::Susceptible));
+
+        Ok(())
+    }
+}
@@ -1,13 +1,18 @@
 // Provides wasm bindings for the v0 engine.
 use wasm_bindgen::prelude::*;
 
-use crate::v0::config::{DiseaseSpreadParameters, WorldConfig};
+use crate::v0::checkpoint::{SimulationRng, WorldCheckpoint};
+use crate::v0::config::WorldConfig;
 use crate::v0::core;
 use crate::v0::geometry::BoundingBox;
+use crate::v0::interventions::InterventionRecord;
+use crate::v0::transmission::{
+    household_secondary_attack_rate, TransmissionEvent, TransmissionTree,
+};
+use crate::v0::triggers::PolicyActivation;
 use crate::v0::types::Mask;
 use anyhow::Result;
-use rand::RngCore;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Debug, Copy, Clone)]
 pub enum DiseaseState {
@@ -17,8 +22,14 @@ pub enum DiseaseState {
     #[serde(rename = "exposed")]
     Exposed,
 
-    #[serde(rename = "infectious")]
-    Infectious,
+    #[serde(rename = "presymptomatic")]
+    Presymptomatic,
+
+    #[serde(rename = "asymptomatic")]
+    Asymptomatic,
+
+    #[serde(rename = "symptomatic")]
+    Symptomatic,
 
     #[serde(rename = "recovered")]
     Recovered,
@@ -29,8 +40,10 @@ impl DiseaseState {
         match ds {
             core::DiseaseState::Susceptible => DiseaseState::Susceptible,
             core::DiseaseState::Exposed(_) => DiseaseState::Exposed,
-            core::DiseaseState::Infectious(_) => DiseaseState::Infectious,
-            core::DiseaseState::Recovered => DiseaseState::Recovered,
+            core::DiseaseState::Presymptomatic(_) => DiseaseState::Presymptomatic,
+            core::DiseaseState::Asymptomatic(_) => DiseaseState::Asymptomatic,
+            core::DiseaseState::Symptomatic(_) => DiseaseState::Symptomatic,
+            core::DiseaseState::Recovered(_) => DiseaseState::Recovered,
         }
     }
 }
@@ -52,6 +65,19 @@ impl HouseholdState {
     }
 }
 
+// Queueing outside a store that is at its maximum occupancy.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct StoreQueueStats {
+    // Sum of the queue length over every tick, and the longest the queue got.
+    pub total_queue_length: usize,
+    pub max_queue_length: usize,
+
+    // Number of shoppers who waited in the queue, and how long they waited in total and at most.
+    pub num_waits: usize,
+    pub total_wait_ticks: usize,
+    pub max_wait_ticks: usize,
+}
+
 #[derive(Serialize)]
 pub struct Person {
     pub id: usize,
@@ -74,8 +100,10 @@ pub struct Person {
 #[derive(Serialize)]
 pub struct State {
     pub tick: usize,
+    pub time_of_day: Option<usize>,
     pub people: Vec<Person>,
     pub households: Vec<HouseholdState>,
+    pub num_vaccinated: usize,
 }
 
 #[wasm_bindgen]
@@ -84,12 +112,66 @@ pub struct WorldView {
 }
 
 impl WorldView {
-    pub fn new(config: WorldConfig, rng: Box<dyn RngCore>) -> Result<Self> {
+    pub fn new(config: WorldConfig, rng: Box<dyn SimulationRng>) -> Result<Self> {
         let world = core::World::new(rng, config)?;
 
         Ok(WorldView { world })
     }
 
+    pub fn save(&self) -> Result<WorldCheckpoint> {
+        self.world.checkpoint()
+    }
+
+    pub fn load(checkpoint: WorldCheckpoint) -> Result<Self> {
+        let world = core::World::from_checkpoint(checkpoint)?;
+
+        Ok(WorldView { world })
+    }
+
+    pub fn get_transmission_events(&self) -> &[TransmissionEvent] {
+        &self.world.transmission_events
+    }
+
+    pub fn get_intervention_records(&self) -> &[InterventionRecord] {
+        &self.world.intervention_records
+    }
+
+    pub fn get_policy_activations(&self) -> &[PolicyActivation] {
+        &self.world.policy_activations
+    }
+
+    // Number of shopping trips that arrived at each store. Empty without a shopper behavior.
+    pub fn get_store_visits(&self) -> Vec<usize> {
+        self.world
+            .person_behavior
+            .get_store_visits()
+            .map_or(vec![], |visits| visits.to_vec())
+    }
+
+    // Queue lengths and wait times outside each store. Empty without a shopper behavior.
+    pub fn get_store_queue_stats(&self) -> Vec<StoreQueueStats> {
+        self.world
+            .person_behavior
+            .get_store_queue_stats()
+            .map_or(vec![], |stats| stats.to_vec())
+    }
+
+    // Without a map, everyone is in the same household.
+    pub fn get_household_secondary_attack_rate(&self) -> Option<f32> {
+        self.world.map.as_ref()?;
+        let households = self
+            .world
+            .people
+            .iter()
+            .map(|p| p.household_idx)
+            .collect::<Vec<_>>();
+        household_secondary_attack_rate(&self.world.transmission_events, &households)
+    }
+
+    pub fn get_transmission_tree(&self) -> TransmissionTree {
+        TransmissionTree::from_events(&self.world.transmission_events)
+    }
+
     pub fn get_state(&self) -> State {
         let people = self
             .world
@@ -119,8 +201,14 @@ impl WorldView {
 
         State {
             tick: self.world.tick,
+            time_of_day: self.world.time_of_day(),
             people,
             households,
+            num_vaccinated: self
+                .world
+                .vaccination_campaign
+                .as_ref()
+                .map_or(0, |campaign| campaign.num_vaccinated()),
         }
     }
 }
@@ -138,20 +226,27 @@ impl WorldView {
         JsValue::from_serde(&state).unwrap()
     }
 
+    pub fn transmission_events_to_json(&self) -> JsValue {
+        JsValue::from_serde(self.get_transmission_events()).unwrap()
+    }
+
+    // Fails for worlds that were created without a seed, since their rng cannot be saved.
+    pub fn save_to_json(&self) -> Result<String, JsValue> {
+        let checkpoint = self
+            .save()
+            .map_err(|e| JsValue::from_str(&format!("failed to save world: {}", e)))?;
+
+        serde_json::to_string(&checkpoint)
+            .map_err(|e| JsValue::from_str(&format!("failed to serialize world: {}", e)))
+    }
+
     pub fn get_background_viral_particles(&mut self) -> js_sys::Float32Array {
-        match self.world.config.disease_parameters.spread_parameters {
-            DiseaseSpreadParameters::InfectionRadius(_) => unimplemented!(),
-            DiseaseSpreadParameters::BackgroundViralParticle(_) => {
-                return unsafe {
-                    js_sys::Float32Array::view(
-                        self.world
-                            .disease_spreader
-                            .get_background_viral_levels()
-                            .as_slice(),
-                    )
-                }
-            }
-        };
+        let levels = self
+            .world
+            .disease_spreader
+            .get_background_viral_levels()
+            .expect("spread model has no background viral particles");
+        unsafe { js_sys::Float32Array::view(levels.as_slice()) }
     }
 
     pub fn get_households(&self) -> JsValue {
@@ -0,0 +1,263 @@
+// Vaccination campaigns, shared by the v0 and city engines.
+//
+// Times are in the engine's own unit: ticks for the v0 engine, and hours for the city engine.
+
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+// Order in which people are vaccinated.
+// The attribute type is specific to each engine.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum VaccinationPriority<A> {
+    #[serde(rename = "random")]
+    Random,
+
+    // Households are vaccinated in a random order, with everyone in a household vaccinated
+    // together.
+    #[serde(rename = "by_household")]
+    ByHousehold,
+
+    // People with the attribute are vaccinated first, followed by everyone else.
+    #[serde(rename = "by_attribute")]
+    ByAttribute(A),
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub struct VaccineEfficacy {
+    // Reduction in a vaccinated person's risk of being infected, once the vaccine is fully
+    // effective. 0 means no protection, 1 means that they can never be infected.
+    pub susceptibility_reduction: f32,
+
+    // Reduction in how infectious a vaccinated person is if they are infected anyway.
+    pub infectiousness_reduction: f32,
+
+    // How long after the dose the vaccine becomes fully effective. Protection ramps up linearly
+    // until then.
+    pub ramp_up_time: usize,
+}
+
+impl VaccineEfficacy {
+    pub fn is_valid(&self) -> bool {
+        (0.0..=1.0).contains(&self.susceptibility_reduction)
+            && (0.0..=1.0).contains(&self.infectiousness_reduction)
+    }
+
+    // Fraction of the full protection that a person vaccinated at dose_time has at time.
+    fn protection(&self, dose_time: usize, time: usize) -> f32 {
+        let time_since_dose = time.saturating_sub(dose_time);
+        if time_since_dose >= self.ramp_up_time {
+            1.0
+        } else {
+            time_since_dose as f32 / self.ramp_up_time as f32
+        }
+    }
+
+    // Multiplier on the risk of infection of a person who was vaccinated at dose_time, if at all.
+    pub fn susceptibility_multiplier(&self, dose_time: Option<usize>, time: usize) -> f32 {
+        match dose_time {
+            Some(dose_time) => {
+                1.0 - self.susceptibility_reduction * self.protection(dose_time, time)
+            }
+            None => 1.0,
+        }
+    }
+
+    // Multiplier on the infectiousness of a person who was vaccinated at dose_time, if at all.
+    pub fn infectiousness_multiplier(&self, dose_time: Option<usize>, time: usize) -> f32 {
+        match dose_time {
+            Some(dose_time) => {
+                1.0 - self.infectiousness_reduction * self.protection(dose_time, time)
+            }
+            None => 1.0,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone)]
+pub struct VaccinationParams<A> {
+    // Doses are given out at the start of each day, beginning at this time.
+    pub start_time: usize,
+
+    // Maximum number of doses given out each day.
+    pub doses_per_day: usize,
+
+    pub priority: VaccinationPriority<A>,
+    pub efficacy: VaccineEfficacy,
+}
+
+// What a campaign needs to know about each person to prioritize them.
+pub struct VaccinationCandidate {
+    pub household: usize,
+    // Whether the person has the attribute that the campaign prioritizes, if any.
+    pub has_attribute: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VaccinationCampaign {
+    // Indexes of people, in the order that they will be vaccinated.
+    queue: Vec<usize>,
+    // Number of people at the front of the queue who have been vaccinated.
+    num_vaccinated: usize,
+
+    // Time of the next round of doses.
+    next_dose_time: usize,
+    time_per_day: usize,
+    doses_per_day: usize,
+    pub efficacy: VaccineEfficacy,
+}
+
+impl VaccinationCampaign {
+    // candidates is indexed by person.
+    pub fn new<A>(
+        params: &VaccinationParams<A>,
+        time_per_day: usize,
+        candidates: &[VaccinationCandidate],
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        assert!(time_per_day > 0);
+
+        let mut queue = (0..candidates.len()).collect::<Vec<_>>();
+        queue.shuffle(rng);
+        match params.priority {
+            VaccinationPriority::Random => {}
+            VaccinationPriority::ByHousehold => {
+                let mut households = candidates.iter().map(|c| c.household).collect::<Vec<_>>();
+                households.sort_unstable();
+                households.dedup();
+                let mut household_order = vec![0; households.last().map_or(0, |h| h + 1)];
+
+                households.shuffle(rng);
+                for (order, household) in households.into_iter().enumerate() {
+                    household_order[household] = order;
+                }
+                queue.sort_by_key(|&idx| (household_order[candidates[idx].household], idx));
+            }
+            VaccinationPriority::ByAttribute(_) => {
+                // The sort is stable, so each group stays in a random order.
+                queue.sort_by_key(|&idx| !candidates[idx].has_attribute);
+            }
+        }
+
+        VaccinationCampaign {
+            queue,
+            num_vaccinated: 0,
+            next_dose_time: params.start_time,
+            time_per_day,
+            doses_per_day: params.doses_per_day,
+            efficacy: params.efficacy,
+        }
+    }
+
+    // Returns the people to vaccinate at this time.
+    pub fn next_doses(&mut self, time: usize) -> &[usize] {
+        if time < self.next_dose_time {
+            return &[];
+        }
+        self.next_dose_time += self.time_per_day;
+
+        let start = self.num_vaccinated;
+        self.num_vaccinated = (start + self.doses_per_day).min(self.queue.len());
+        &self.queue[start..self.num_vaccinated]
+    }
+
+    pub fn num_vaccinated(&self) -> usize {
+        self.num_vaccinated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn params(doses_per_day: usize, priority: VaccinationPriority<()>) -> VaccinationParams<()> {
+        VaccinationParams {
+            start_time: 10,
+            doses_per_day,
+            priority,
+            efficacy: VaccineEfficacy {
+                susceptibility_reduction: 0.8,
+                infectiousness_reduction: 0.5,
+                ramp_up_time: 4,
+            },
+        }
+    }
+
+    fn candidates() -> Vec<VaccinationCandidate> {
+        (0..12)
+            .map(|idx| VaccinationCandidate {
+                household: idx / 3,
+                has_attribute: idx % 4 == 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_doses_per_day() {
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let mut campaign = VaccinationCampaign::new(
+            &params(5, VaccinationPriority::Random),
+            24,
+            &candidates(),
+            &mut rng,
+        );
+
+        assert!(campaign.next_doses(0).is_empty());
+        assert_eq!(campaign.next_doses(10).len(), 5);
+        assert!(campaign.next_doses(11).is_empty());
+        assert_eq!(campaign.next_doses(34).len(), 5);
+        assert_eq!(campaign.next_doses(58).len(), 2);
+        assert!(campaign.next_doses(82).is_empty());
+        assert_eq!(campaign.num_vaccinated(), 12);
+
+        let mut vaccinated = campaign.queue.clone();
+        vaccinated.sort_unstable();
+        assert_eq!(vaccinated, (0..12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_priority() {
+        let mut rng = ChaCha8Rng::seed_from_u64(10914);
+        let candidates = candidates();
+
+        let campaign = VaccinationCampaign::new(
+            &params(3, VaccinationPriority::ByHousehold),
+            24,
+            &candidates,
+            &mut rng,
+        );
+        for household_idxs in campaign.queue.chunks(3) {
+            let household = candidates[household_idxs[0]].household;
+            assert!(household_idxs
+                .iter()
+                .all(|&idx| candidates[idx].household == household));
+        }
+
+        let campaign = VaccinationCampaign::new(
+            &params(3, VaccinationPriority::ByAttribute(())),
+            24,
+            &candidates,
+            &mut rng,
+        );
+        assert!(campaign.queue[..3]
+            .iter()
+            .all(|&idx| candidates[idx].has_attribute));
+        assert!(campaign.queue[3..]
+            .iter()
+            .all(|&idx| !candidates[idx].has_attribute));
+    }
+
+    #[test]
+    fn test_efficacy_ramps_up() {
+        let efficacy = params(1, VaccinationPriority::Random).efficacy;
+
+        assert_eq!(efficacy.susceptibility_multiplier(None, 100), 1.0);
+        assert_eq!(efficacy.susceptibility_multiplier(Some(10), 10), 1.0);
+        approx::assert_ulps_eq!(efficacy.susceptibility_multiplier(Some(10), 12), 0.6);
+        approx::assert_ulps_eq!(efficacy.infectiousness_multiplier(Some(10), 12), 0.75);
+        assert_eq!(efficacy.infectiousness_multiplier(Some(10), 14), 0.5);
+        assert_eq!(efficacy.infectiousness_multiplier(Some(10), 100), 0.5);
+    }
+}
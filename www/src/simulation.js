@@ -10,7 +10,9 @@ export const height = 400;
 const color_map = {
   "susceptible": 0xB8F7BF,
   "exposed": 0xC7BA29,
-  "infectious": 0xEB6383,
+  "presymptomatic": 0xF2A0B3,
+  "asymptomatic": 0xA75FB8,
+  "symptomatic": 0xEB6383,
   "recovered": 0xC8C8C8,
 };
 
@@ -233,6 +235,9 @@ export class Simulation {
         }),
       },
     };
+    mask_geo_and_material["cloth"] = mask_geo_and_material["regular"];
+    mask_geo_and_material["surgical"] = mask_geo_and_material["regular"];
+    mask_geo_and_material["respirator"] = mask_geo_and_material["n95"];
 
     this.people_by_id = new Map();
     for (const person_state of world.to_json()["people"]) {
@@ -323,7 +328,9 @@ export class Simulation {
     let counts = {
       "susceptible": 0,
       "exposed": 0,
-      "infectious": 0,
+      "presymptomatic": 0,
+      "asymptomatic": 0,
+      "symptomatic": 0,
       "recovered": 0,
     };
     for (const person of people) {
@@ -331,7 +338,7 @@ export class Simulation {
     }
 
     const a = counts["exposed"];
-    const b = a + counts["infectious"];
+    const b = a + counts["presymptomatic"] + counts["asymptomatic"] + counts["symptomatic"];
     const c = b + counts["susceptible"];
     const d = c + counts["recovered"];
 
@@ -407,7 +414,9 @@ export class Simulation {
     this.animate_households(state["households"]);
 
     let spread_params = this.config["engine_config"]["disease_parameters"]["spread_parameters"];
-    if ('background_viral_particle' in spread_params) {
+    const has_particles = (params) => 'background_viral_particle' in params
+      || ('composite' in params && params['composite'].some(has_particles));
+    if (has_particles(spread_params)) {
       this.update_background_viral_particles();
     }
 